@@ -0,0 +1,312 @@
+// Оглавление архивов без распаковки: .zip читаем сами, .7z и .rar - через внешний 7z.
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::commands::scanner;
+use crate::engines::{self, DirListing};
+use crate::titles;
+
+pub const ARCHIVE_EXTENSIONS: &[&str] = &["zip", "7z", "rar"];
+
+// Исполняемый файл 7-Zip. Ищется один раз: без него .7z и .rar просто пропускаются.
+static SEVEN_ZIP: Lazy<Option<PathBuf>> = Lazy::new(|| {
+    let found = find_seven_zip();
+    if found.is_none() {
+        eprintln!("7z не найден: архивы .7z и .rar не будут просмотрены");
+    }
+    found
+});
+
+fn find_seven_zip() -> Option<PathBuf> {
+    let names: &[&str] = if cfg!(windows) {
+        &["7z.exe", "7za.exe", "7zz.exe"]
+    } else {
+        &["7z", "7zz", "7za"]
+    };
+    let mut dirs: Vec<PathBuf> = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).collect())
+        .unwrap_or_default();
+    if cfg!(windows) {
+        // Установщик 7-Zip не добавляет себя в PATH
+        for var in ["ProgramFiles", "ProgramFiles(x86)"] {
+            if let Some(dir) = std::env::var_os(var) {
+                dirs.push(PathBuf::from(dir).join("7-Zip"));
+            }
+        }
+    }
+    dirs.iter()
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|path| path.is_file())
+}
+
+// Архив с узнаваемой игрой внутри, которую еще не распаковали
+#[derive(Debug, Serialize, Clone)]
+pub struct ArchiveCandidate {
+    pub path: String,
+    pub size: u64,
+    // "zip", "7z" или "rar"
+    pub format: String,
+    pub title: String,
+    pub version: Option<String>,
+    pub engine: String,
+    // Папка игры внутри архива ("" - корень архива)
+    pub game_dir: String,
+}
+
+// Многотомные RAR: смотрим только первый том
+pub fn is_archive(path: &Path) -> bool {
+    let Some(extension) = path.extension().and_then(|e| e.to_str()) else {
+        return false;
+    };
+    let extension = extension.to_lowercase();
+    if !ARCHIVE_EXTENSIONS.contains(&extension.as_str()) {
+        return false;
+    }
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match stem.rsplit_once(".part") {
+        Some((_, part)) if part.chars().all(|c| c.is_ascii_digit()) => {
+            part.trim_start_matches('0') == "1"
+        }
+        _ => true,
+    }
+}
+
+// Пути всех записей архива через "/", с флагом "это папка"
+fn list_entries(path: &Path, format: &str) -> Result<Vec<(String, bool)>, String> {
+    if format == "zip" {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+        return Ok(archive
+            .file_names()
+            .map(|name| {
+                let name = name.replace('\\', "/");
+                let is_dir = name.ends_with('/');
+                (name.trim_end_matches('/').to_string(), is_dir)
+            })
+            .collect());
+    }
+
+    let Some(seven_zip) = SEVEN_ZIP.as_ref() else {
+        return Ok(Vec::new());
+    };
+    let output = Command::new(seven_zip)
+        .arg("l")
+        .arg("-slt")
+        .arg(path)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(parse_seven_zip_listing(&String::from_utf8_lossy(&output.stdout)))
+}
+
+// Вывод `7z l -slt`: сначала блок про сам архив, после строки "----------" -
+// по блоку "Ключ = значение" на каждую запись, блоки разделены пустой строкой
+fn parse_seven_zip_listing(output: &str) -> Vec<(String, bool)> {
+    let Some((_, entries)) = output.split_once("----------") else {
+        return Vec::new();
+    };
+    entries
+        .replace("\r\n", "\n")
+        .split("\n\n")
+        .filter_map(|block| {
+            let mut path = None;
+            let mut is_dir = false;
+            for line in block.lines() {
+                let Some((key, value)) = line.split_once(" = ") else { continue };
+                match key.trim() {
+                    "Path" => path = Some(value.replace('\\', "/")),
+                    "Folder" => is_dir = value.trim() == "+",
+                    "Attributes" => is_dir |= value.trim_start().starts_with('D'),
+                    _ => {}
+                }
+            }
+            path.map(|path| (path, is_dir))
+        })
+        .collect()
+}
+
+// Содержимое каждой папки архива: путь папки -> (имя, это папка)
+fn directory_tree(entries: &[(String, bool)]) -> BTreeMap<String, BTreeSet<(String, bool)>> {
+    let mut tree: BTreeMap<String, BTreeSet<(String, bool)>> = BTreeMap::new();
+    for (path, is_dir) in entries {
+        let parts: Vec<&str> = path.split('/').filter(|part| !part.is_empty()).collect();
+        for (i, part) in parts.iter().enumerate() {
+            // Промежуточные папки в zip часто не записаны отдельно
+            let part_is_dir = i + 1 < parts.len() || *is_dir;
+            tree.entry(parts[..i].join("/"))
+                .or_default()
+                .insert((part.to_string(), part_is_dir));
+        }
+    }
+    tree
+}
+
+// Смотрит архив и возвращает кандидата, если внутри узнается структура игры.
+// Ошибка - архив битый или 7z не смог его прочитать.
+pub fn inspect(path: &Path) -> Result<Option<ArchiveCandidate>, String> {
+    let format = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let entries = list_entries(path, &format)?;
+    let tree = directory_tree(&entries);
+
+    // Самая близкая к корню папка с узнаваемой игрой
+    let Some((game_dir, engine)) = tree
+        .iter()
+        .filter_map(|(dir, children)| {
+            let listing = DirListing::from_entries(children.iter().cloned());
+            engines::detect_engine(&listing)
+                // index.html в архиве - чаще документация, чем игра
+                .filter(|engine| *engine != "html")
+                .map(|engine| (dir.clone(), engine))
+        })
+        .min_by_key(|(dir, _)| (dir.matches('/').count() + !dir.is_empty() as usize, dir.clone()))
+    else {
+        return Ok(None);
+    };
+
+    let archive_stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    // Имя папки игры обычно осмысленнее имени архива ("Game-1.0-pc" против "download (3)").
+    // Если игра лежит в корне архива, пробуем имя ее единственного .exe.
+    let exe_stems: Vec<&str> = tree
+        .get(&game_dir)
+        .into_iter()
+        .flatten()
+        .filter(|(_, is_dir)| !is_dir)
+        .filter_map(|(name, _)| exe_stem(name))
+        .filter(|stem| !stem.to_lowercase().starts_with("unitycrashhandler"))
+        .collect();
+    let name = match (game_dir.rsplit('/').next(), exe_stems.as_slice()) {
+        (Some(dir_name), _) if !dir_name.is_empty() => dir_name,
+        (_, [exe_stem]) => exe_stem,
+        _ => &archive_stem,
+    };
+    let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    Ok(Some(ArchiveCandidate {
+        path: path.to_string_lossy().into_owned(),
+        size,
+        format,
+        title: titles::guess_title(name),
+        version: scanner::find_version_in_name(name)
+            .or_else(|| scanner::find_version_in_name(&archive_stem)),
+        engine: engine.to_string(),
+        game_dir,
+    }))
+}
+
+// Имя без расширения .exe в любом регистре: в сборках под Windows часто бывает "Game.EXE"
+fn exe_stem(name: &str) -> Option<&str> {
+    let split = name.len().checked_sub(".exe".len())?;
+    name.get(split..)
+        .filter(|extension| extension.eq_ignore_ascii_case(".exe"))
+        .and_then(|_| name.get(..split))
+}
+
+// Архив считается установленным, если рядом уже лежит папка с тем же именем,
+// что у архива или у папки игры внутри него
+pub fn is_extracted_next_to(candidate: &ArchiveCandidate) -> bool {
+    let path = Path::new(&candidate.path);
+    let Some(parent) = path.parent() else {
+        return false;
+    };
+    let top_dir = candidate.game_dir.split('/').next().unwrap_or_default();
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    [stem.as_str(), top_dir]
+        .iter()
+        .filter(|name| !name.is_empty())
+        .any(|name| parent.join(name).is_dir())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Вывод `7z l -slt` (7-Zip 16.02) для архива с папкой, файлом с пробелами в имени и .EXE
+    const SEVEN_ZIP_LISTING: &str = "\
+7-Zip [64] 16.02 : Copyright (c) 1999-2016 Igor Pavlov : 2016-05-21
+p7zip Version 16.02 (locale=en_US.UTF-8,Utf16=on,HugeFiles=on,64 bits,8 CPUs)
+
+Scanning the drive for archives:
+1 file, 1048576 bytes (1024 KiB)
+
+Listing archive: Game-1.0-pc.7z
+
+--
+Path = Game-1.0-pc.7z
+Type = 7z
+Physical Size = 1048576
+Headers Size = 250
+Method = LZMA2:24
+Solid = +
+Blocks = 1
+
+----------
+Path = Game-1.0-pc
+Size = 0
+Packed Size = 0
+Modified = 2024-03-01 12:00:00
+Attributes = D....
+CRC = 
+Encrypted = -
+Method = 
+Block = 
+
+Path = Game-1.0-pc\\Game Data\\level 1.dat
+Size = 524288
+Packed Size = 1048326
+Modified = 2024-03-01 12:00:00
+Attributes = A....
+CRC = 1A2B3C4D
+Encrypted = -
+Method = LZMA2:24
+Block = 0
+
+Path = Game-1.0-pc\\Game.EXE
+Size = 524288
+Packed Size = 
+Modified = 2024-03-01 12:00:00
+Attributes = A....
+CRC = 5E6F7A8B
+Encrypted = -
+Method = LZMA2:24
+Block = 0
+
+";
+
+    #[test]
+    fn parses_seven_zip_listing() {
+        assert_eq!(
+            parse_seven_zip_listing(SEVEN_ZIP_LISTING),
+            vec![
+                ("Game-1.0-pc".to_string(), true),
+                ("Game-1.0-pc/Game Data/level 1.dat".to_string(), false),
+                ("Game-1.0-pc/Game.EXE".to_string(), false),
+            ]
+        );
+        let crlf = SEVEN_ZIP_LISTING.replace('\n', "\r\n");
+        assert_eq!(parse_seven_zip_listing(&crlf).len(), 3);
+        assert!(parse_seven_zip_listing("ERROR: not an archive").is_empty());
+    }
+
+    #[test]
+    fn exe_stem_ignores_case() {
+        assert_eq!(exe_stem("Game.EXE"), Some("Game"));
+        assert_eq!(exe_stem("game.exe"), Some("game"));
+        assert_eq!(exe_stem("Игра.Exe"), Some("Игра"));
+        assert_eq!(exe_stem("readme.txt"), None);
+        assert_eq!(exe_stem("exe"), None);
+    }
+}
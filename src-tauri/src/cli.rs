@@ -0,0 +1,324 @@
+use serde::Serialize;
+use std::env;
+use std::path::PathBuf;
+
+use crate::commands::{backups, icons, launcher, library, scanner};
+use crate::database::{self, GameEntry, LibraryRoot};
+use crate::instance::{self, Forwarded};
+use crate::scan_rules::ScanRules;
+
+// Должен совпадать с identifier в tauri.conf.json: по нему Tauri строит путь к данным
+const APP_IDENTIFIER: &str = "dev.revenger.RVNLauncher";
+
+const USAGE: &str = "Использование: rvnlauncher [команда]
+
+Без команды открывается окно лаунчера.
+
+Команды:
+  launch <номер|путь|имя> [--profile <id>]   запустить игру и дождаться ее завершения
+  list [--all]                               показать игры библиотеки (--all - вместе со скрытыми)
+  scan <папка> [--deep] [--dry-run]          найти игры в папке и добавить их в библиотеку
+  backup <номер|путь|имя>                    сделать резервную копию сохранений
+  export [файл]                              выгрузить библиотеку в JSON (по умолчанию в stdout)
+  roots list                                 показать папки библиотеки
+  roots add <папка> [--deep]                 добавить папку в библиотеку
+  roots remove <номер>                       убрать папку из библиотеки (игры остаются)
+  roots rescan [--apply]                     пересканировать папки библиотеки
+                                             (--apply - добавить новые игры и перенести перемещенные)
+  help                                       показать эту справку";
+
+const COMMANDS: &[&str] = &[
+    "launch", "list", "scan", "backup", "export", "roots", "help", "--help", "-h",
+];
+
+// Библиотека в том виде, в котором ее выгружает `export`
+#[derive(Serialize)]
+struct LibraryExport {
+    games: Vec<GameEntry>,
+    folders: Vec<FolderExport>,
+}
+
+#[derive(Serialize)]
+struct FolderExport {
+    name: String,
+    games: Vec<String>,
+}
+
+// Разбирает аргументы командной строки. Возвращает код выхода, если это была команда CLI,
+// и None, если нужно открыть окно.
+pub fn run(args: &[String]) -> Option<i32> {
+    let command = args.first()?.as_str();
+    // Незнакомые аргументы могут прийти от самой ОС или Tauri - тогда открываем окно
+    if !COMMANDS.contains(&command) {
+        return None;
+    }
+    attach_console();
+
+    let args = &args[1..];
+    let result = match command {
+        "launch" => launch(args),
+        "list" => list(args),
+        "scan" => scan(args),
+        "backup" => backup(args),
+        "export" => export(args),
+        "roots" => roots(args),
+        _ => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+    };
+    match result {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("Ошибка: {}", e);
+            Some(1)
+        }
+    }
+}
+
+// В release-сборке на Windows у приложения нет своей консоли,
+// поэтому вывод CLI нужно направить в консоль, из которой его запустили
+#[cfg(windows)]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+// Тот же путь, что возвращает app.path().app_data_dir() в окне
+pub fn app_data_dir() -> Result<PathBuf, String> {
+    let home = || env::var_os("HOME").map(PathBuf::from);
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| home().map(|home| home.join(".local/share")))
+    };
+    base.map(|dir| dir.join(APP_IDENTIFIER))
+        .ok_or_else(|| "Не удалось определить папку данных приложения".to_string())
+}
+
+fn open_database() -> Result<PathBuf, String> {
+    let app_data_dir = app_data_dir()?;
+    database::init_at(&app_data_dir);
+    Ok(app_data_dir)
+}
+
+// Значение флага вида `--name value`
+fn option_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|index| args.get(index + 1))
+        .map(|value| value.as_str())
+}
+
+// Первый позиционный аргумент (не флаг и не значение флага)
+fn positional<'a>(args: &'a [String], flags_with_values: &[&str]) -> Option<&'a str> {
+    let mut skip_next = false;
+    for arg in args {
+        if skip_next {
+            skip_next = false;
+        } else if flags_with_values.contains(&arg.as_str()) {
+            skip_next = true;
+        } else if !arg.starts_with("--") {
+            return Some(arg);
+        }
+    }
+    None
+}
+
+fn launch(args: &[String]) -> Result<(), String> {
+    let game = positional(args, &["--profile"]).ok_or("Укажите номер, путь или имя игры")?;
+    let profile_id = option_value(args, "--profile")
+        .map(|id| id.parse::<i64>().map_err(|_| format!("Некорректный номер профиля: {}", id)))
+        .transpose()?;
+
+    // Если окно уже открыто, игру запускает оно: так сессия попадет в его реестр
+    let app_data_dir = app_data_dir()?;
+    match instance::forward_launch(&app_data_dir, game, profile_id) {
+        Forwarded::Launched(pid) => {
+            println!("Игра запущена окном лаунчера, PID {}", pid);
+            return Ok(());
+        }
+        Forwarded::Failed(e) => return Err(e),
+        Forwarded::NoInstance => {}
+    }
+
+    let app_data_dir = open_database()?;
+    let game = database::resolve_game(game)?;
+    let (pid, tracker) = launcher::start_game(None, &app_data_dir, &game.path, profile_id)?;
+    println!("{} запущена, PID {}", game.name, pid);
+    // Ждем конца сессии, иначе время игры не запишется
+    tracker
+        .join()
+        .map_err(|_| "Поток учета сессии завершился с ошибкой".to_string())
+}
+
+fn format_play_time(seconds: i64) -> String {
+    format!("{}:{:02}", seconds / 3600, seconds % 3600 / 60)
+}
+
+fn list(args: &[String]) -> Result<(), String> {
+    let show_hidden = args.iter().any(|arg| arg == "--all");
+    let app_data_dir = open_database()?;
+    let games = database::load_games(&app_data_dir, None)?;
+
+    for (id, path) in database::list_game_ids()? {
+        let Some(game) = games.iter().find(|game| game.path == path) else {
+            continue;
+        };
+        if game.is_hidden && !show_hidden {
+            continue;
+        }
+        println!(
+            "{}\t{}\t{}\t{}\t{}",
+            id,
+            game.name,
+            game.version.as_deref().unwrap_or("-"),
+            format_play_time(game.play_time_seconds),
+            game.path
+        );
+    }
+    Ok(())
+}
+
+fn scan(args: &[String]) -> Result<(), String> {
+    let dir = positional(args, &[]).ok_or("Укажите папку для сканирования")?;
+    let deep_search = args.iter().any(|arg| arg == "--deep");
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+
+    // Правила сканирования хранятся в базе, поэтому она нужна и для --dry-run
+    let app_data_dir = open_database()?;
+    let found = scanner::scan_directory(dir, deep_search, &ScanRules::load());
+
+    let mut added = 0;
+    for info in found.games.iter().map(|info| info.to_game_entry()) {
+        let status = if dry_run {
+            "найдена"
+        } else if database::find_game(&info.path)?.is_some() {
+            // Повторное добавление дозаполнит движок, если раньше его определить не удалось
+            database::db_add_game(info.clone())?;
+            "уже в библиотеке"
+        } else {
+            database::db_add_game(info.clone())?;
+            if let Err(e) = icons::extract_icon_for_game(&app_data_dir, &info) {
+                eprintln!("Иконка для {} не извлечена: {}", info.path, e);
+            }
+            added += 1;
+            "добавлена"
+        };
+        println!("{}\t{}\t{}", status, info.name, info.path);
+    }
+    for archive in &found.archives {
+        println!("не установлена\t{}\t{}", archive.title, archive.path);
+    }
+    if !dry_run {
+        eprintln!("Найдено игр: {}, добавлено: {}", found.games.len(), added);
+    }
+    Ok(())
+}
+
+fn backup(args: &[String]) -> Result<(), String> {
+    let game = positional(args, &[]).ok_or("Укажите номер, путь или имя игры")?;
+    let app_data_dir = open_database()?;
+    let game = database::resolve_game(game)?;
+    let backup_path = backups::create_backup(&app_data_dir, &game.path)?;
+    println!("{}", backup_path.to_string_lossy());
+    Ok(())
+}
+
+fn export(args: &[String]) -> Result<(), String> {
+    let app_data_dir = open_database()?;
+    let games = database::load_games(&app_data_dir, None)?;
+    let folders = database::db_get_all_folders()?
+        .into_iter()
+        .map(|folder| {
+            let games = database::load_games(&app_data_dir, Some(folder.id))?;
+            Ok(FolderExport {
+                name: folder.name,
+                games: games.into_iter().map(|game| game.path).collect(),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let json = serde_json::to_string_pretty(&LibraryExport { games, folders })
+        .map_err(|e| e.to_string())?;
+    match positional(args, &[]) {
+        Some(file) => std::fs::write(file, json).map_err(|e| e.to_string()),
+        None => {
+            println!("{}", json);
+            Ok(())
+        }
+    }
+}
+
+fn roots(args: &[String]) -> Result<(), String> {
+    let action = args.first().map(String::as_str).ok_or("Укажите действие: list, add, remove или rescan")?;
+    let args = &args[1..];
+    let app_data_dir = open_database()?;
+    match action {
+        "list" => {
+            for root in database::db_get_library_roots()? {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    root.id.unwrap_or_default(),
+                    if root.deep_search { "глубоко" } else { "-" },
+                    root.last_scan_at.as_deref().unwrap_or("-"),
+                    root.path
+                );
+            }
+            Ok(())
+        }
+        "add" => {
+            let dir = positional(args, &[]).ok_or("Укажите папку")?;
+            // Храним абсолютный путь: по нему потом сравниваются пути игр
+            let path = std::fs::canonicalize(dir).map_err(|e| format!("{}: {}", dir, e))?;
+            let id = database::db_save_library_root(LibraryRoot {
+                id: None,
+                path: path.to_string_lossy().into_owned(),
+                deep_search: args.iter().any(|arg| arg == "--deep"),
+                dir_blacklist: Vec::new(),
+                file_blacklist: Vec::new(),
+                last_scan_at: None,
+            })?;
+            println!("{}\t{}", id, path.to_string_lossy());
+            Ok(())
+        }
+        "remove" => {
+            let id = positional(args, &[]).ok_or("Укажите номер папки")?;
+            let id = id.parse::<i64>().map_err(|_| format!("Некорректный номер папки: {}", id))?;
+            database::db_delete_library_root(id)
+        }
+        "rescan" => {
+            let apply = args.iter().any(|arg| arg == "--apply");
+            let report = library::rescan(&app_data_dir, apply)?;
+            let added_status = if apply { "добавлена" } else { "новая" };
+            for info in &report.added {
+                let entry = info.to_game_entry();
+                println!("{}\t{}\t{}", added_status, entry.name, entry.path);
+            }
+            for moved in &report.moved {
+                println!("перемещена\t{}\t{} -> {}", moved.name, moved.from, moved.to);
+            }
+            for game in &report.disappeared {
+                println!("пропала\t{}\t{}", game.name, game.path);
+            }
+            for archive in &report.not_installed {
+                println!("не установлена\t{}\t{}", archive.title, archive.path);
+            }
+            for root in &report.unavailable_roots {
+                println!("недоступна\t-\t{}", root);
+            }
+            Ok(())
+        }
+        _ => Err(format!("Неизвестное действие: {}", action)),
+    }
+}
@@ -1,6 +1,6 @@
 use serde::Serialize;
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fs;
 use tauri::{AppHandle, Manager};
 use walkdir::WalkDir;
@@ -8,6 +8,8 @@ use zip::write::{FileOptions, ZipWriter};
 use std::time::SystemTime;
 use chrono::{DateTime, Local};
 
+use crate::database;
+
 
 #[derive(Debug, Serialize, Clone)]
 pub struct BackupInfo {
@@ -18,38 +20,72 @@ pub struct BackupInfo {
 
 #[tauri::command]
 pub async fn backup_saves(app: AppHandle, game_path: String) -> Result<String, String> {
-    // 1. Определяем путь к сохранениям Ren'Py
     let app_data_dir = app.path().app_data_dir().expect("Не удалось найти AppData");
+    let backup_path = create_backup(&app_data_dir, &game_path)?;
+    Ok(format!("Бэкап успешно создан: {:?}", backup_path))
+}
+
+// Папка сохранений Ren'Py рядом с исполняемым файлом игры
+fn saves_dir_for(game_path: &str) -> Result<PathBuf, String> {
+    Ok(Path::new(game_path)
+        .parent()
+        .ok_or_else(|| "Не найден родительский каталог игры".to_string())?
+        .join("game")
+        .join("saves"))
+}
+
+// Отпечаток содержимого папки сохранений: пути, размеры и время изменения файлов.
+// Используем FNV-1a, чтобы значение не менялось между версиями Rust (оно хранится в базе).
+fn saves_fingerprint(saves_dir: &Path) -> String {
+    let mut files: Vec<(String, u64, u128)> = WalkDir::new(saves_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let modified = metadata
+                .modified()
+                .ok()?
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .ok()?
+                .as_nanos();
+            let name = entry.path().strip_prefix(saves_dir).ok()?.to_string_lossy().into_owned();
+            Some((name, metadata.len(), modified))
+        })
+        .collect();
+    files.sort();
+
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for (name, size, modified) in files {
+        let record = format!("{}|{}|{}\n", name, size, modified);
+        for byte in record.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
 
+// Создает ZIP-архив с сохранениями игры и возвращает путь к нему
+pub fn create_backup(app_data_dir: &Path, game_path: &str) -> Result<PathBuf, String> {
     // Извлекаем имя папки с игрой из полного пути к .exe
     // Пример: E:\Games\MyGame\MyGame.exe -> MyGame
-    let game_folder_name = Path::new(&game_path)
-        .parent()
-        .expect("Не найден родительский каталог игры")
-        .file_name()
-        .expect("Не найдено имя каталога игры")
-        .to_str()
-        .expect("Некорректное имя каталога игры");
-
-    // Собираем путь к папке сохранений Ren'Py
-    let saves_dir = Path::new(&game_path)
+    let game_folder_name = Path::new(game_path)
         .parent()
-        .expect("Не найден родительский каталог игры")
-        .join("game")
-        .join("saves");
+        .and_then(|p| p.file_name())
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| "Не удалось извлечь имя папки игры".to_string())?;
 
+    let saves_dir = saves_dir_for(game_path)?;
     if !saves_dir.exists() {
         return Err(format!("Папка сохранений не найдена: {:?}", saves_dir));
     }
 
-    // 2. Создаем папку для бэкапов, если ее нет
-    let backup_root_dir = app_data_dir.join("backups");
-    std::fs::create_dir_all(&backup_root_dir).map_err(|e| e.to_string())?;
-
-    let backup_game_dir = backup_root_dir.join(game_folder_name);
+    // Создаем папку для бэкапов, если ее нет
+    let backup_game_dir = app_data_dir.join("backups").join(game_folder_name);
     std::fs::create_dir_all(&backup_game_dir).map_err(|e| e.to_string())?;
 
-    // 3. Создаем ZIP-архив
+    // Создаем ZIP-архив
     let timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
     let backup_file_name = format!("{}.zip", timestamp);
     let backup_path = backup_game_dir.join(backup_file_name);
@@ -63,7 +99,7 @@ pub async fn backup_saves(app: AppHandle, game_path: String) -> Result<String, S
     let walker = WalkDir::new(&saves_dir).into_iter();
     for entry in walker.filter_map(|e| e.ok()) {
         let path = entry.path();
-        let name = path.strip_prefix(Path::new(&saves_dir)).unwrap();
+        let name = path.strip_prefix(&saves_dir).unwrap();
 
         if path.is_file() {
             zip.start_file(name.to_str().unwrap(), options)
@@ -80,7 +116,27 @@ pub async fn backup_saves(app: AppHandle, game_path: String) -> Result<String, S
 
     zip.finish().map_err(|e| e.to_string())?;
 
-    Ok(format!("Бэкап успешно создан: {:?}", backup_path))
+    // Запоминаем состояние сохранений, чтобы автобэкап не дублировал этот архив
+    database::set_backup_fingerprint(game_path, &saves_fingerprint(&saves_dir))?;
+
+    Ok(backup_path)
+}
+
+// Автоматический бэкап после выхода из игры. Возвращает путь к архиву,
+// если бэкап был создан, и None, если он выключен или сохранения не менялись.
+pub fn auto_backup_after_session(app_data_dir: &Path, game_path: &str) -> Result<Option<PathBuf>, String> {
+    if !database::auto_backup_enabled(game_path)? {
+        return Ok(None);
+    }
+    let saves_dir = saves_dir_for(game_path)?;
+    if !saves_dir.exists() {
+        return Ok(None);
+    }
+    let fingerprint = saves_fingerprint(&saves_dir);
+    if database::backup_fingerprint(game_path)?.as_deref() == Some(fingerprint.as_str()) {
+        return Ok(None);
+    }
+    create_backup(app_data_dir, game_path).map(Some)
 }
 
 #[tauri::command]
@@ -0,0 +1,89 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::database::SessionCrash;
+
+// Отчеты, которые Ren'Py пишет в папку игры при падении: traceback.txt - исключение
+// во время игры, errors.txt - ошибки в скриптах при загрузке.
+// log.txt обновляется при каждом запуске, поэтому сам по себе признаком падения не является.
+const CRASH_REPORTS: &[&str] = &["traceback.txt", "errors.txt"];
+
+// Часы файловой системы (особенно сетевой) могут немного расходиться с нашими
+const MODIFIED_SLACK: Duration = Duration::from_secs(2);
+
+// Ищет отчет о падении, созданный или измененный после начала сессии
+pub fn detect_renpy_crash(game_dir: &Path, session_started: SystemTime) -> Option<SessionCrash> {
+    let threshold = session_started.checked_sub(MODIFIED_SLACK).unwrap_or(session_started);
+    CRASH_REPORTS.iter().find_map(|name| {
+        let path = game_dir.join(name);
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+        if modified < threshold {
+            return None;
+        }
+        // Ren'Py пишет отчеты в UTF-8 с BOM
+        let bytes = fs::read(&path).ok()?;
+        let text = String::from_utf8_lossy(&bytes);
+        let mut crash = if *name == "traceback.txt" {
+            parse_traceback(&text)
+        } else {
+            parse_errors(&text)
+        };
+        crash.report_path = path.to_string_lossy().into_owned();
+        Some(crash)
+    })
+}
+
+// Разбирает строку вида `File "game/script.rpy", line 12, in script`
+// или `File "game/script.rpy", line 5: expected statement.`
+fn parse_file_line(line: &str) -> Option<(String, i64, &str)> {
+    let rest = line.trim_start().strip_prefix("File \"")?;
+    let (file, rest) = rest.split_once('"')?;
+    let rest = rest.strip_prefix(", line ")?;
+    let digits_end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    let line_number = rest[..digits_end].parse().ok()?;
+    Some((file.to_string(), line_number, &rest[digits_end..]))
+}
+
+fn parse_traceback(text: &str) -> SessionCrash {
+    // Сначала идет короткий трейсбек по скриптам игры, за ним полный по коду Ren'Py
+    let short = text.split("-- Full Traceback").next().unwrap_or(text);
+    let mut crash = SessionCrash::default();
+
+    // Самый полезный кадр - последний в .rpy: именно там стоит упавшая строка скрипта
+    for line in short.lines() {
+        if let Some((file, line_number, _)) = parse_file_line(line) {
+            if file.ends_with(".rpy") || crash.script_file.is_none() {
+                crash.script_file = Some(file);
+                crash.script_line = Some(line_number);
+            }
+        }
+    }
+    // Исключение - последняя строка без отступа, например `NameError: name 'x' is not defined`
+    crash.exception = short
+        .lines()
+        .rev()
+        .map(|line| line.trim_start_matches('\u{feff}'))
+        .find(|line| {
+            !line.trim().is_empty()
+                && !line.starts_with(char::is_whitespace)
+                && !line.starts_with("I'm sorry")
+                && !line.ends_with(':')
+        })
+        .map(|line| line.trim().to_string());
+    crash
+}
+
+fn parse_errors(text: &str) -> SessionCrash {
+    let mut crash = SessionCrash::default();
+    // Берем первую ошибку: остальные часто следуют из нее
+    if let Some((file, line_number, message)) = text.lines().find_map(parse_file_line) {
+        crash.script_file = Some(file);
+        crash.script_line = Some(line_number);
+        let message = message.trim_start_matches(':').trim();
+        if !message.is_empty() {
+            crash.exception = Some(message.to_string());
+        }
+    }
+    crash
+}
@@ -0,0 +1,192 @@
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::database::{self, GameEntry, GameHook};
+use crate::process_tree::{self, ProcessTree};
+
+// Сколько вывода хука сохраняем в журнал (хвост)
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+// Сколько ждем, пока дочитается вывод после завершения хука.
+// Фоновый процесс, запущенный хуком, может держать pipe открытым бесконечно.
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+// Как часто обновляем дерево процессов хука, пока он работает
+const TREE_REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+struct HookOutcome {
+    exit_code: Option<i32>,
+    timed_out: bool,
+    duration_ms: i64,
+    output: String,
+}
+
+impl HookOutcome {
+    fn succeeded(&self) -> bool {
+        !self.timed_out && self.exit_code == Some(0)
+    }
+}
+
+// Контекст, который передается хуку через переменные окружения
+pub struct HookContext<'a> {
+    pub game: &'a GameEntry,
+    pub session_id: Option<i64>,
+    pub exit_code: Option<i32>,
+}
+
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+fn run_hook(hook: &GameHook, context: &HookContext) -> HookOutcome {
+    let started_at = Instant::now();
+    let game_dir = Path::new(&context.game.path).parent().unwrap_or(Path::new("."));
+
+    let mut command = shell_command(&hook.command);
+    command
+        .current_dir(game_dir)
+        .env("RVN_HOOK_STAGE", &hook.stage)
+        .env("RVN_GAME_PATH", &context.game.path)
+        .env("RVN_GAME_NAME", &context.game.name)
+        .env("RVN_GAME_DIR", game_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(session_id) = context.session_id {
+        command.env("RVN_SESSION_ID", session_id.to_string());
+    }
+    if let Some(exit_code) = context.exit_code {
+        command.env("RVN_EXIT_CODE", exit_code.to_string());
+    }
+    // Своя группа процессов: по таймауту убиваем не только sh, но и все, что он запустил
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            return HookOutcome {
+                exit_code: None,
+                timed_out: false,
+                duration_ms: started_at.elapsed().as_millis() as i64,
+                output: format!("Не удалось запустить хук: {}", e),
+            }
+        }
+    };
+
+    // На Windows групп процессов нет: потомков хука находим по дереву процессов
+    let mut tree = ProcessTree::new(child.id());
+    let mut last_tree_refresh = Instant::now();
+
+    // stdout и stderr читаем в отдельных потоках в общий буфер, чтобы хук не завис на полном pipe
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let finished_readers = Arc::new(AtomicUsize::new(0));
+    let streams: Vec<Box<dyn Read + Send>> = [
+        child.stdout.take().map(|s| Box::new(s) as Box<dyn Read + Send>),
+        child.stderr.take().map(|s| Box::new(s) as Box<dyn Read + Send>),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let readers_count = streams.len();
+    for mut stream in streams {
+        let output = Arc::clone(&output);
+        let finished_readers = Arc::clone(&finished_readers);
+        std::thread::spawn(move || {
+            let mut chunk = [0u8; 4096];
+            while let Ok(n) = stream.read(&mut chunk) {
+                if n == 0 {
+                    break;
+                }
+                let mut buffer = output.lock().unwrap();
+                buffer.extend_from_slice(&chunk[..n]);
+                if buffer.len() > MAX_OUTPUT_BYTES {
+                    let excess = buffer.len() - MAX_OUTPUT_BYTES;
+                    buffer.drain(..excess);
+                }
+            }
+            finished_readers.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+
+    let timeout = Duration::from_secs(hook.timeout_seconds.max(1) as u64);
+    let mut timed_out = false;
+    let exit_code = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status.code(),
+            Ok(None) if started_at.elapsed() >= timeout => {
+                timed_out = true;
+                #[cfg(unix)]
+                process_tree::kill_process_group(child.id());
+                process_tree::terminate(&tree.refresh(), true);
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+            Ok(None) => {
+                if last_tree_refresh.elapsed() >= TREE_REFRESH_INTERVAL {
+                    tree.refresh();
+                    last_tree_refresh = Instant::now();
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Err(_) => break None,
+        }
+    };
+
+    let drain_started = Instant::now();
+    while finished_readers.load(Ordering::SeqCst) < readers_count
+        && drain_started.elapsed() < OUTPUT_DRAIN_TIMEOUT
+    {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    let output = String::from_utf8_lossy(&output.lock().unwrap()).into_owned();
+
+    HookOutcome {
+        exit_code,
+        timed_out,
+        duration_ms: started_at.elapsed().as_millis() as i64,
+        output,
+    }
+}
+
+// Выполняет хуки этапа по очереди и записывает результат каждого в журнал.
+// Возвращает ошибку, если упал хук с abort_on_failure (для pre_launch это отменяет запуск).
+pub fn run_hooks(stage: &str, context: &HookContext) -> Result<(), String> {
+    let hooks = database::find_hooks(&context.game.path, stage)?;
+    for hook in hooks {
+        let outcome = run_hook(&hook, context);
+        if let Err(e) = database::record_hook_run(
+            hook.id.unwrap_or_default(),
+            &context.game.path,
+            context.session_id,
+            stage,
+            outcome.duration_ms,
+            outcome.exit_code,
+            outcome.timed_out,
+            &outcome.output,
+        ) {
+            eprintln!("Не удалось записать результат хука {:?}: {}", hook.id, e);
+        }
+
+        if !outcome.succeeded() && hook.abort_on_failure {
+            let reason = if outcome.timed_out {
+                format!("превышено время ожидания ({} сек.)", hook.timeout_seconds)
+            } else {
+                format!("код выхода {:?}", outcome.exit_code)
+            };
+            return Err(format!("Хук \"{}\" завершился с ошибкой: {}", hook.command, reason));
+        }
+    }
+    Ok(())
+}
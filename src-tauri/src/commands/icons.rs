@@ -0,0 +1,86 @@
+use image::ImageFormat;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Manager};
+
+use crate::database::{self, GameEntry};
+use crate::pe_icon;
+
+// Исполняемый файл Windows, из которого берем иконку. Для .sh/.py-запуска
+// (Ren'Py на Linux) рядом обычно лежит .exe с тем же именем.
+fn icon_source(game: &GameEntry) -> Option<PathBuf> {
+    let path = Path::new(&game.path);
+    if game.game_type == "exe" {
+        return Some(path.to_path_buf());
+    }
+    let sibling = path.with_extension("exe");
+    sibling.is_file().then_some(sibling)
+}
+
+// Достает иконку из .exe игры в папку icons и записывает ее игре, если своей иконки у нее нет.
+// Возвращает относительный путь к иконке или None, если иконки в файле нет.
+// Файлы без иконки запоминаются и больше не читаются, пока не изменятся.
+pub fn extract_icon_for_game(app_data_dir: &Path, game: &GameEntry) -> Result<Option<String>, String> {
+    if game.icon_path.as_deref().is_some_and(|path| !path.is_empty()) {
+        return Ok(None);
+    }
+    let Some(source) = icon_source(game) else {
+        return Ok(None);
+    };
+    let mut file = std::fs::File::open(&source).map_err(|e| e.to_string())?;
+    let modified_at = file
+        .metadata()
+        .and_then(|m| m.modified())
+        .map_err(|e| e.to_string())?
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    let source_path = source.to_string_lossy();
+    if database::icon_failed(&source_path, modified_at)? {
+        return Ok(None);
+    }
+    let image = pe_icon::extract_largest_icon(&mut file)
+        .and_then(|ico| image::load_from_memory_with_format(&ico, ImageFormat::Ico).ok());
+    let Some(image) = image else {
+        database::record_icon_failure(&source_path, modified_at)?;
+        return Ok(None);
+    };
+
+    let icons_dir = app_data_dir.join("icons");
+    std::fs::create_dir_all(&icons_dir).map_err(|e| e.to_string())?;
+    // То же имя файла, что и у иконок из буфера обмена
+    let safe_game_path = game.path.replace(|c: char| !c.is_alphanumeric(), "_");
+    let file_name = format!("{}.png", safe_game_path);
+    image
+        .save_with_format(icons_dir.join(&file_name), ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+
+    let icon_path = Path::new("icons").join(file_name).to_string_lossy().into_owned();
+    database::db_update_game_icon(game.path.clone(), icon_path.clone())?;
+    Ok(Some(icon_path))
+}
+
+// Иконки из .exe для одной игры или, если game_path не указан, для всех игр без иконки.
+// Возвращает количество игр, получивших иконку.
+#[tauri::command]
+pub async fn extract_exe_icons(app: AppHandle, game_path: Option<String>) -> Result<usize, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Some(path) = game_path {
+            let game = database::find_game(&path)?
+                .ok_or_else(|| format!("Игра не найдена в библиотеке: {}", path))?;
+            return Ok(extract_icon_for_game(&app_data_dir, &game)?.map_or(0, |_| 1));
+        }
+        let mut extracted = 0;
+        for game in database::load_games(&app_data_dir, None)? {
+            match extract_icon_for_game(&app_data_dir, &game) {
+                Ok(Some(_)) => extracted += 1,
+                Ok(None) => {}
+                // Один битый .exe не должен мешать остальным
+                Err(e) => eprintln!("Иконка для {} не извлечена: {}", game.path, e),
+            }
+        }
+        Ok(extracted)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
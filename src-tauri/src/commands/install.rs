@@ -0,0 +1,379 @@
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::{icons, scanner};
+use crate::database::{self, GameEntry};
+use crate::scan_rules::ScanRules;
+
+// Флаги отмены запущенных установок по их id
+static ACTIVE_INSTALLS: Lazy<Mutex<HashMap<u64, Arc<AtomicBool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_INSTALL_ID: AtomicU64 = AtomicU64::new(1);
+// Папки игр, в которые сейчас идет установка: две установки одной игры
+// распаковывали бы в одну временную папку и удаляли бы ее друг у друга
+static INSTALL_TARGETS: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct InstallProgress {
+    extracted_bytes: u64,
+    total_bytes: u64,
+    files_done: usize,
+    files_total: usize,
+    current_file: String,
+}
+
+#[derive(Serialize, Clone, Default)]
+pub struct InstallOutcome {
+    // Папка, в которую распакована игра (None после отмены)
+    game_dir: Option<String>,
+    games: Vec<GameEntry>,
+    cancelled: bool,
+    // Игра распакована, но что-то после этого не удалось (например, запись в базу).
+    // Папка игры при этом остается: ее можно добавить сканированием.
+    errors: Vec<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum InstallEvent {
+    Progress(InstallProgress),
+    Finished(InstallOutcome),
+    Failed(String),
+}
+
+// Файл архива и путь, куда его распаковать (относительно папки игры)
+struct PlannedEntry {
+    index: usize,
+    target: PathBuf,
+    is_dir: bool,
+    size: u64,
+    unix_mode: Option<u32>,
+}
+
+// Занятая папка игры; освобождается при выходе из install_archive, в том числе по ошибке
+struct TargetLock(PathBuf);
+
+impl TargetLock {
+    fn acquire(game_dir: &Path) -> Result<Self, String> {
+        if !INSTALL_TARGETS.lock().unwrap().insert(game_dir.to_path_buf()) {
+            return Err(format!("В эту папку уже идет установка: {:?}", game_dir));
+        }
+        Ok(TargetLock(game_dir.to_path_buf()))
+    }
+}
+
+impl Drop for TargetLock {
+    fn drop(&mut self) {
+        INSTALL_TARGETS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.0);
+    }
+}
+
+// Если все содержимое лежит в одной папке верхнего уровня, возвращает ее имя:
+// такую папку не создаем второй раз внутри папки игры
+fn single_top_dir(paths: &[(PathBuf, bool)]) -> Option<String> {
+    let mut top: Option<&std::ffi::OsStr> = None;
+    for (path, is_dir) in paths {
+        let mut components = path.components();
+        let first = components.next()?.as_os_str();
+        // Файл прямо в корне архива - папка верхнего уровня не единственная
+        if components.next().is_none() && !is_dir {
+            return None;
+        }
+        match top {
+            Some(top) if top != first => return None,
+            _ => top = Some(first),
+        }
+    }
+    top.map(|top| top.to_string_lossy().into_owned())
+}
+
+// Права на Unix берем из архива. В архивах, собранных на Windows, бита исполнения нет
+// вовсе, поэтому скрипты запуска и Linux-бинарники Ren'Py делаем исполняемыми сами.
+#[cfg(unix)]
+fn apply_permissions(path: &Path, unix_mode: Option<u32>) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let in_linux_dir = path
+        .parent()
+        .is_some_and(|parent| parent.to_string_lossy().to_lowercase().contains("linux"));
+    let is_launcher = file_name.ends_with(".sh") || file_name.ends_with(".py");
+
+    let mut mode = unix_mode.map_or(0o644, |mode| mode & 0o777) | 0o600;
+    if is_launcher || (in_linux_dir && !file_name.contains('.')) {
+        mode |= 0o755;
+    }
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn apply_permissions(_path: &Path, _unix_mode: Option<u32>) -> std::io::Result<()> {
+    Ok(())
+}
+
+// Распаковывает .zip в новую папку внутри root_path, затем сканирует ее и добавляет игры.
+// Распаковка идет во временную папку, которая переименовывается только в самом конце:
+// после отмены или ошибки в библиотеке не остается недораспакованной игры.
+pub fn install_archive(
+    app_data_dir: &Path,
+    archive_path: &Path,
+    root_path: &Path,
+    cancel: &AtomicBool,
+    on_progress: &dyn Fn(&InstallProgress),
+) -> Result<InstallOutcome, String> {
+    let extension = archive_path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if extension != "zip" {
+        return Err(format!("Установка поддерживается только из .zip, а не .{}", extension));
+    }
+    if !root_path.is_dir() {
+        return Err(format!("Папка библиотеки не найдена: {:?}", root_path));
+    }
+
+    let file = fs::File::open(archive_path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+
+    // enclosed_name отбрасывает абсолютные пути и выход через "..": такие архивы не ставим вовсе
+    let mut paths = Vec::with_capacity(archive.len());
+    for index in 0..archive.len() {
+        let entry = archive.by_index(index).map_err(|e| e.to_string())?;
+        let path = entry
+            .enclosed_name()
+            .ok_or_else(|| format!("Архив содержит небезопасный путь: {}", entry.name()))?;
+        if entry.is_symlink() {
+            return Err(format!("Архив содержит символическую ссылку: {}", entry.name()));
+        }
+        paths.push((path, entry.is_dir(), entry.size(), entry.unix_mode()));
+    }
+    let path_kinds: Vec<(PathBuf, bool)> = paths.iter().map(|(p, d, _, _)| (p.clone(), *d)).collect();
+    let top_dir = single_top_dir(&path_kinds);
+
+    let dir_name = match &top_dir {
+        Some(top_dir) => top_dir.clone(),
+        None => archive_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .ok_or("Не удалось определить имя папки игры")?,
+    };
+    let game_dir = root_path.join(&dir_name);
+    let _lock = TargetLock::acquire(&game_dir)?;
+    if game_dir.exists() {
+        return Err(format!("Папка уже существует: {:?}", game_dir));
+    }
+    let partial_dir = root_path.join(format!(".{}.partial", dir_name));
+
+    let plan: Vec<PlannedEntry> = paths
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, (path, is_dir, size, unix_mode))| {
+            let target = match &top_dir {
+                Some(_) => path.components().skip(1).collect::<PathBuf>(),
+                None => path,
+            };
+            // Сама папка верхнего уровня после выравнивания становится пустым путем
+            let is_empty = target.components().all(|c| matches!(c, Component::CurDir));
+            (!is_empty).then_some(PlannedEntry { index, target, is_dir, size, unix_mode })
+        })
+        .collect();
+
+    let mut progress = InstallProgress {
+        total_bytes: plan.iter().map(|entry| entry.size).sum(),
+        files_total: plan.iter().filter(|entry| !entry.is_dir).count(),
+        ..Default::default()
+    };
+
+    let extracted = (|| -> Result<bool, String> {
+        // Остатки прерванной установки
+        if partial_dir.exists() {
+            fs::remove_dir_all(&partial_dir).map_err(|e| e.to_string())?;
+        }
+        fs::create_dir_all(&partial_dir).map_err(|e| e.to_string())?;
+        let mut last_report = Instant::now();
+        let mut buffer = vec![0u8; 64 * 1024];
+        for planned in &plan {
+            let target = partial_dir.join(&planned.target);
+            if planned.is_dir {
+                fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let mut entry = archive.by_index(planned.index).map_err(|e| e.to_string())?;
+            let mut output = fs::File::create(&target).map_err(|e| e.to_string())?;
+            loop {
+                if cancel.load(Ordering::Relaxed) {
+                    return Ok(false);
+                }
+                let read = entry.read(&mut buffer).map_err(|e| e.to_string())?;
+                if read == 0 {
+                    break;
+                }
+                output.write_all(&buffer[..read]).map_err(|e| e.to_string())?;
+                progress.extracted_bytes += read as u64;
+                if last_report.elapsed() >= PROGRESS_INTERVAL {
+                    progress.current_file = planned.target.to_string_lossy().into_owned();
+                    on_progress(&progress);
+                    last_report = Instant::now();
+                }
+            }
+            apply_permissions(&target, planned.unix_mode).map_err(|e| e.to_string())?;
+            progress.files_done += 1;
+        }
+        progress.current_file.clear();
+        on_progress(&progress);
+        Ok(true)
+    })();
+
+    match extracted {
+        // Папку могли создать в обход лаунчера, пока шла распаковка; пустую rename молча заменил бы
+        Ok(true) if game_dir.exists() => {
+            let _ = fs::remove_dir_all(&partial_dir);
+            return Err(format!("Папка уже существует: {:?}", game_dir));
+        }
+        Ok(true) => {
+            if let Err(e) = fs::rename(&partial_dir, &game_dir) {
+                let _ = fs::remove_dir_all(&partial_dir);
+                return Err(e.to_string());
+            }
+        }
+        result => {
+            let _ = fs::remove_dir_all(&partial_dir);
+            result?;
+            return Ok(InstallOutcome {
+                cancelled: true,
+                ..Default::default()
+            });
+        }
+    }
+
+    // Игра уже на месте: дальше ошибки не отменяют установку, а попадают в отчет
+    let mut errors = Vec::new();
+    // Правила папки библиотеки, если игра ставится в одну из них
+    let mut rules = ScanRules::load();
+    match database::db_get_library_roots() {
+        Ok(roots) => {
+            if let Some(root) = roots.into_iter().find(|root| Path::new(&root.path) == root_path) {
+                rules = rules.with_globs(&root.dir_blacklist, &root.file_blacklist);
+            }
+        }
+        Err(e) => eprintln!("Не удалось загрузить папки библиотеки: {}", e),
+    }
+    let found = scanner::scan_directory(&game_dir.to_string_lossy(), true, &rules);
+    let mut games = Vec::new();
+    for info in found.games {
+        let entry = info.to_game_entry();
+        if let Err(e) = database::db_add_game(entry.clone()) {
+            errors.push(format!("{} не добавлена в библиотеку: {}", entry.path, e));
+            continue;
+        }
+        if let Err(e) = icons::extract_icon_for_game(app_data_dir, &entry) {
+            eprintln!("Иконка для {} не извлечена: {}", entry.path, e);
+        }
+        games.push(entry);
+    }
+    Ok(InstallOutcome {
+        game_dir: Some(game_dir.to_string_lossy().into_owned()),
+        games,
+        cancelled: false,
+        errors,
+    })
+}
+
+// Установка в фоне: сразу возвращает id (для cancel_install),
+// прогресс и результат приходят через on_event
+#[tauri::command]
+pub fn install_from_archive(
+    app: AppHandle,
+    archive_path: String,
+    root_path: String,
+    on_event: Channel<InstallEvent>,
+) -> Result<u64, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let install_id = NEXT_INSTALL_ID.fetch_add(1, Ordering::SeqCst);
+    let cancel = Arc::new(AtomicBool::new(false));
+    ACTIVE_INSTALLS.lock().unwrap().insert(install_id, cancel.clone());
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut guard = InstallGuard {
+            install_id,
+            app,
+            on_event,
+            result: None,
+        };
+        let result = install_archive(
+            &app_data_dir,
+            Path::new(&archive_path),
+            Path::new(&root_path),
+            &cancel,
+            &|progress| {
+                let _ = guard.on_event.send(InstallEvent::Progress(progress.clone()));
+            },
+        );
+        guard.result = Some(result);
+    });
+    Ok(install_id)
+}
+
+// Завершает установку при выходе из потока, в том числе по панике при распаковке:
+// установка пропадает из ACTIVE_INSTALLS, и фронтенд всегда получает Finished или Failed
+struct InstallGuard {
+    install_id: u64,
+    app: AppHandle,
+    on_event: Channel<InstallEvent>,
+    result: Option<Result<InstallOutcome, String>>,
+}
+
+impl Drop for InstallGuard {
+    fn drop(&mut self) {
+        ACTIVE_INSTALLS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.install_id);
+        let result = self.result.take().unwrap_or_else(|| {
+            eprintln!("Установка {} прервана ошибкой", self.install_id);
+            Err("Установка прервана внутренней ошибкой".to_string())
+        });
+        match result {
+            Ok(outcome) => {
+                for game in &outcome.games {
+                    let _ = self.app.emit("library-game-added", game);
+                }
+                let _ = self.on_event.send(InstallEvent::Finished(outcome));
+            }
+            Err(e) => {
+                let _ = self.on_event.send(InstallEvent::Failed(e));
+            }
+        }
+    }
+}
+
+// Возвращает false, если установка уже закончилась
+#[tauri::command]
+pub fn cancel_install(install_id: u64) -> bool {
+    match ACTIVE_INSTALLS.lock().unwrap().get(&install_id) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
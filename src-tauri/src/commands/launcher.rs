@@ -0,0 +1,506 @@
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::backups;
+use crate::commands::crashes;
+use crate::commands::hooks::{self, HookContext};
+use crate::commands::logs;
+use crate::commands::runners;
+use crate::database::{self, GameEntry, LaunchProfile, SessionCrash};
+use crate::process_tree::{self, ProcessTree};
+
+// Как часто проверяем, живы ли процессы игры
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+// Как часто сохраняем прогресс сессии в базу на случай сбоя
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+// Сколько ждем мягкого завершения игры перед принудительным
+const DEFAULT_STOP_TIMEOUT_SECS: u64 = 10;
+
+// Запущенная игра. Отдается фронтенду и приходит в событии game-session-started.
+#[derive(Debug, Serialize, Clone)]
+pub struct RunningGame {
+    game_path: String,
+    name: String,
+    session_id: i64,
+    pid: u32,
+    started_at: String,
+}
+
+enum RunningEntry {
+    // Место занято на время pre_launch-хуков и запуска процесса: второй одновременный
+    // запуск той же игры (двойной клик, CLI вместе с окном) получит ошибку
+    Starting,
+    Running {
+        info: RunningGame,
+        // Живые процессы дерева игры, обновляются трекером сессии
+        pids: Vec<u32>,
+    },
+}
+
+// Резерв Starting в реестре. Если запуск не дошел до конца (ошибка хука, профиля или spawn),
+// резерв снимается при выходе из start_game.
+struct StartingSlot {
+    game_path: String,
+    committed: bool,
+}
+
+impl StartingSlot {
+    fn reserve(game: &GameEntry) -> Result<Self, String> {
+        let mut running = RUNNING_GAMES.lock().unwrap();
+        if running.contains_key(&game.path) {
+            return Err(format!("Игра уже запущена: {}", game.name));
+        }
+        running.insert(game.path.clone(), RunningEntry::Starting);
+        Ok(StartingSlot {
+            game_path: game.path.clone(),
+            committed: false,
+        })
+    }
+
+    fn commit(mut self, info: RunningGame) {
+        let pids = vec![info.pid];
+        RUNNING_GAMES
+            .lock()
+            .unwrap()
+            .insert(self.game_path.clone(), RunningEntry::Running { info, pids });
+        self.committed = true;
+    }
+}
+
+impl Drop for StartingSlot {
+    fn drop(&mut self) {
+        if !self.committed {
+            RUNNING_GAMES.lock().unwrap().remove(&self.game_path);
+        }
+    }
+}
+
+// Реестр запущенных игр. Живет в бэкенде, поэтому переживает перезагрузку webview
+// и доступен из любого окна.
+static RUNNING_GAMES: Lazy<Mutex<HashMap<String, RunningEntry>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Событие, которое получает фронтенд, когда сессия игры закончилась
+#[derive(Debug, Serialize, Clone)]
+pub struct GameExitInfo {
+    game_path: String,
+    session_id: i64,
+    pid: u32,
+    exit_code: Option<i32>,
+    duration_seconds: i64,
+    // Есть, если игра упала и оставила отчет (traceback.txt / errors.txt)
+    crash: Option<SessionCrash>,
+}
+
+// Что именно и как запускать. Собирается из записи игры и ее профиля запуска.
+struct LaunchPlan {
+    program: PathBuf,
+    args: Vec<String>,
+    working_dir: PathBuf,
+    env_vars: Vec<(String, String)>,
+}
+
+impl LaunchPlan {
+    fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .current_dir(&self.working_dir)
+            .envs(self.env_vars.iter().map(|(k, v)| (k, v)))
+            .stdin(Stdio::null());
+        command
+    }
+}
+
+// Ищет интерпретатор Python, который поставляется с Ren'Py-игрой:
+// lib/py3-linux-x86_64/python (Ren'Py 8), lib/py2-... (Ren'Py 7), lib/linux-x86_64/... (старые версии)
+fn find_bundled_python(game_dir: &Path) -> Option<PathBuf> {
+    let platform = if cfg!(windows) {
+        "windows"
+    } else if cfg!(target_os = "macos") {
+        "mac"
+    } else {
+        "linux"
+    };
+    let candidates: &[&str] = if cfg!(windows) {
+        &["pythonw.exe", "python.exe"]
+    } else {
+        &["python", "pythonw"]
+    };
+
+    let mut lib_dirs: Vec<PathBuf> = std::fs::read_dir(game_dir.join("lib"))
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+            path.is_dir() && name.contains(platform) && (name.starts_with("py") || name.starts_with(platform))
+        })
+        .collect();
+    // py3 раньше py2: в играх бывают обе сборки
+    lib_dirs.sort();
+    lib_dirs.reverse();
+
+    lib_dirs
+        .iter()
+        .flat_map(|dir| candidates.iter().map(move |name| dir.join(name)))
+        .find(|path| path.is_file())
+}
+
+fn find_python(game_dir: &Path) -> Result<(PathBuf, Vec<String>), String> {
+    if let Some(bundled) = find_bundled_python(game_dir) {
+        // Так Ren'Py запускает себя в собственных .sh/.exe лаунчерах
+        return Ok((bundled, vec!["-EO".to_string()]));
+    }
+    let configured = database::db_get_setting("pythonPath".to_string())?
+        .filter(|path| !path.trim().is_empty());
+    let python = configured.unwrap_or_else(|| {
+        if cfg!(windows) { "python" } else { "python3" }.to_string()
+    });
+    Ok((PathBuf::from(python), Vec::new()))
+}
+
+fn build_launch_plan(
+    app_data_dir: &Path,
+    game: &GameEntry,
+    profile: Option<&LaunchProfile>,
+) -> Result<LaunchPlan, String> {
+    let executable = Path::new(&game.path);
+    if !executable.is_file() {
+        return Err(format!("Файл игры не найден: {}", game.path));
+    }
+    // Рабочая директория - папка игры, иначе многие движки не находят свои ресурсы
+    let game_dir = executable
+        .parent()
+        .ok_or_else(|| "Не удалось найти родительскую директорию игры".to_string())?;
+    let target = executable.to_string_lossy().into_owned();
+
+    let mut plan = LaunchPlan {
+        program: executable.to_path_buf(),
+        args: Vec::new(),
+        working_dir: game_dir.to_path_buf(),
+        env_vars: Vec::new(),
+    };
+
+    // Команда в терминах Windows, если запускать нужно через Wine/Proton
+    let mut windows_command = None;
+    match game.game_type.as_str() {
+        "exe" if !cfg!(windows) => windows_command = Some(vec![target]),
+        // Через /bin/sh скрипт запустится, даже если у него нет бита исполнения
+        "sh" => {
+            plan.program = PathBuf::from(if cfg!(windows) { "sh" } else { "/bin/sh" });
+            plan.args = vec![target];
+        }
+        "py" => {
+            let (python, python_args) = find_python(game_dir)?;
+            plan.program = python;
+            plan.args = python_args;
+            plan.args.push(target);
+        }
+        "bat" | "cmd" if cfg!(windows) => {
+            plan.program = PathBuf::from("cmd");
+            plan.args = vec!["/C".to_string(), target];
+        }
+        "bat" | "cmd" => {
+            windows_command = Some(vec!["cmd".to_string(), "/c".to_string(), target]);
+        }
+        _ => {}
+    }
+
+    if let Some(windows_command) = windows_command {
+        let (runner, prefix_mode) = database::find_game_runner(&game.path)?;
+        let runner = runner.ok_or_else(|| {
+            "Для запуска Windows-игр нужен Wine или Proton. Добавьте раннер в настройках.".to_string()
+        })?;
+        let prefix_dir = runners::prefix_dir(app_data_dir, &game.path, &prefix_mode);
+        std::fs::create_dir_all(&prefix_dir).map_err(|e| e.to_string())?;
+
+        let invocation = runners::runner_invocation(&runner, &prefix_dir, windows_command);
+        plan.program = invocation.program;
+        plan.args = invocation.args;
+        plan.env_vars = invocation.env_vars;
+    }
+
+    let Some(profile) = profile else {
+        return Ok(plan);
+    };
+
+    plan.args.extend(profile.arguments.iter().cloned());
+    // Переменные профиля идут последними и перекрывают значения раннера (например, WINEPREFIX)
+    plan.env_vars.extend(profile.env_vars.iter().map(|(k, v)| (k.clone(), v.clone())));
+    if let Some(dir) = &profile.working_dir {
+        // Относительный путь считается от папки игры
+        plan.working_dir = game_dir.join(dir);
+        if !plan.working_dir.is_dir() {
+            return Err(format!("Рабочая директория не найдена: {:?}", plan.working_dir));
+        }
+    }
+    if let Some(wrapper) = &profile.wrapper {
+        // Обертка запускает игру как свой аргумент: gamemoderun ./game, taskset -c 0-3 ./game
+        let mut wrapper_parts = shlex::split(wrapper)
+            .filter(|parts| !parts.is_empty())
+            .ok_or_else(|| format!("Некорректная команда-обертка: {}", wrapper))?;
+        let wrapper_program = wrapper_parts.remove(0);
+        let mut args = wrapper_parts;
+        args.push(plan.program.to_string_lossy().into_owned());
+        args.append(&mut plan.args);
+        plan.program = PathBuf::from(wrapper_program);
+        plan.args = args;
+    }
+    Ok(plan)
+}
+
+#[tauri::command]
+pub async fn launch_game(
+    app: AppHandle,
+    game_path: String,
+    profile_id: Option<i64>,
+) -> Result<u32, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    // Хуки pre_launch могут выполняться долго, поэтому не держим async-рантайм
+    tauri::async_runtime::spawn_blocking(move || {
+        start_game(Some(app), &app_data_dir, &game_path, profile_id).map(|(pid, _)| pid)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn emit_event<S: Serialize + Clone>(app: Option<&AppHandle>, event: &str, payload: S) {
+    // Без окна (запуск из CLI) событий никто не ждет
+    let Some(app) = app else { return };
+    if let Err(e) = app.emit(event, payload) {
+        eprintln!("Не удалось отправить событие {}: {}", event, e);
+    }
+}
+
+// Запускает игру и поток, который следит за ее сессией.
+// app равен None, когда игру запускает CLI без окна; тогда он ждет завершения потока сам.
+pub fn start_game(
+    app: Option<AppHandle>,
+    app_data_dir: &Path,
+    game_path: &str,
+    profile_id: Option<i64>,
+) -> Result<(u32, JoinHandle<()>), String> {
+    // Запускать можно только игры, которые есть в библиотеке
+    let game = database::find_game(game_path)?
+        .ok_or_else(|| format!("Игра не найдена в библиотеке: {}", game_path))?;
+    let slot = StartingSlot::reserve(&game)?;
+
+    let profile = database::find_launch_profile(&game.path, profile_id)?;
+    if profile_id.is_some() && profile.is_none() {
+        return Err(format!("Профиль запуска {:?} не найден для этой игры", profile_id));
+    }
+
+    // Хуки выполняются до сборки команды: они могут, например, смонтировать образ с игрой
+    let context = HookContext {
+        game: &game,
+        session_id: None,
+        exit_code: None,
+    };
+    hooks::run_hooks("pre_launch", &context)?;
+
+    let plan = build_launch_plan(app_data_dir, &game, profile.as_ref())?;
+
+    let session_id = database::start_play_session(&game.path, game.version.as_deref())?;
+
+    // Вывод игры пишем в лог сессии, иначе падение при запуске не оставляет следов
+    let mut command = plan.command();
+    let log = logs::open_session_log(app_data_dir, &game.path, session_id)
+        .map_err(|e| eprintln!("Не удалось создать лог сессии {}: {}", session_id, e))
+        .ok();
+    if log.is_some() {
+        // Через pipe, чтобы размер лога ограничивался прямо во время игры
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            // Игра так и не запустилась - сессии и ее лога быть не должно
+            let _ = database::db_delete_play_session(session_id);
+            if let Some((_, log_path)) = &log {
+                let _ = std::fs::remove_file(log_path);
+            }
+            return Err(format!("Не удалось запустить игру: {}", e));
+        }
+    };
+    if let Some((file, log_path)) = log {
+        logs::capture_output(&mut child, file, log_path);
+    }
+    let pid = child.id();
+
+    let info = RunningGame {
+        game_path: game.path.clone(),
+        name: game.name.clone(),
+        session_id,
+        pid,
+        started_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    };
+    slot.commit(info.clone());
+    emit_event(app.as_ref(), "game-session-started", info);
+
+    let app_data_dir = app_data_dir.to_path_buf();
+    let tracker = std::thread::spawn(move || {
+        track_session(app, app_data_dir, game, session_id, child)
+    });
+
+    Ok((pid, tracker))
+}
+
+// Сессия длится, пока жив хотя бы один процесс из дерева игры.
+// По окончании время записывается в базу и фронтенд получает событие.
+fn track_session(
+    app: Option<AppHandle>,
+    app_data_dir: PathBuf,
+    game: GameEntry,
+    session_id: i64,
+    mut child: Child,
+) {
+    let pid = child.id();
+    let started_at = Instant::now();
+    let session_started = SystemTime::now();
+    let mut tree = ProcessTree::new(pid);
+    let mut exit_code = None;
+    let mut root_exited = false;
+    let mut last_checkpoint = Instant::now();
+
+    loop {
+        // Забираем статус корневого процесса, иначе на Unix он останется зомби
+        if !root_exited {
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    exit_code = status.code();
+                    root_exited = true;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    eprintln!("Не удалось получить статус процесса {}: {}", pid, e);
+                    root_exited = true;
+                }
+            }
+        }
+        // Обновляем дерево на каждой итерации: потомков нужно заметить, пока родитель жив
+        let alive = tree.refresh();
+        if root_exited && alive.is_empty() {
+            break;
+        }
+        if let Some(RunningEntry::Running { pids, .. }) = RUNNING_GAMES.lock().unwrap().get_mut(&game.path) {
+            *pids = alive;
+        }
+
+        if last_checkpoint.elapsed() >= CHECKPOINT_INTERVAL {
+            let elapsed = started_at.elapsed().as_secs() as i64;
+            if let Err(e) = database::checkpoint_play_session(session_id, elapsed) {
+                eprintln!("Не удалось сохранить контрольную точку сессии {}: {}", session_id, e);
+            }
+            last_checkpoint = Instant::now();
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    RUNNING_GAMES.lock().unwrap().remove(&game.path);
+
+    let duration_seconds = started_at.elapsed().as_secs() as i64;
+    if let Err(e) = database::finish_play_session(session_id, duration_seconds, exit_code) {
+        eprintln!("Не удалось сохранить сессию {} для {}: {}", session_id, game.path, e);
+    }
+
+    // Отчет о падении отличает вылет от обычного выхода: код выхода у Ren'Py в обоих случаях 0
+    let crash = Path::new(&game.path)
+        .parent()
+        .and_then(|game_dir| crashes::detect_renpy_crash(game_dir, session_started));
+    if let Some(crash) = &crash {
+        if let Err(e) = database::record_session_crash(session_id, crash) {
+            eprintln!("Не удалось записать падение сессии {}: {}", session_id, e);
+        }
+    }
+
+    let info = GameExitInfo {
+        game_path: game.path.clone(),
+        session_id,
+        pid,
+        exit_code,
+        duration_seconds,
+        crash,
+    };
+    emit_event(app.as_ref(), "game-session-ended", info);
+
+    match backups::auto_backup_after_session(&app_data_dir, &game.path) {
+        Ok(Some(backup_path)) => {
+            emit_event(app.as_ref(), "backup-created", backup_path.to_string_lossy().into_owned());
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("Автобэкап для {} не удался: {}", game.path, e),
+    }
+
+    let context = HookContext {
+        game: &game,
+        session_id: Some(session_id),
+        exit_code,
+    };
+    if let Err(e) = hooks::run_hooks("post_exit", &context) {
+        eprintln!("{}", e);
+    }
+}
+
+#[tauri::command]
+pub fn list_running_games() -> Vec<RunningGame> {
+    RUNNING_GAMES
+        .lock()
+        .unwrap()
+        .values()
+        .filter_map(|entry| match entry {
+            RunningEntry::Running { info, .. } => Some(info.clone()),
+            RunningEntry::Starting => None,
+        })
+        .collect()
+}
+
+fn running_pids(game_path: &str) -> Option<Vec<u32>> {
+    RUNNING_GAMES
+        .lock()
+        .unwrap()
+        .get(game_path)
+        .and_then(|entry| match entry {
+            RunningEntry::Running { pids, .. } => Some(pids.clone()),
+            RunningEntry::Starting => None,
+        })
+}
+
+// Сначала просит игру закрыться, а если она не успела за timeout_seconds - убивает все ее процессы.
+// Возвращает true, если понадобилось принудительное завершение.
+#[tauri::command]
+pub async fn stop_game(game_path: String, timeout_seconds: Option<u64>) -> Result<bool, String> {
+    let pids = running_pids(&game_path).ok_or_else(|| format!("Игра не запущена: {}", game_path))?;
+    let timeout = Duration::from_secs(timeout_seconds.unwrap_or(DEFAULT_STOP_TIMEOUT_SECS));
+
+    tauri::async_runtime::spawn_blocking(move || {
+        process_tree::terminate(&pids, false);
+
+        // Сессию из реестра убирает трекер, когда все процессы игры завершатся
+        let requested_at = Instant::now();
+        while requested_at.elapsed() < timeout {
+            if running_pids(&game_path).is_none() {
+                return false;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+
+        match running_pids(&game_path) {
+            Some(pids) => {
+                process_tree::terminate(&pids, true);
+                true
+            }
+            None => false,
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
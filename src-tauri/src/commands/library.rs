@@ -0,0 +1,184 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::archives::ArchiveCandidate;
+use crate::commands::{icons, logs, runners};
+use crate::commands::scanner::{self, GameInfo};
+use crate::database::{self, GameEntry};
+use crate::scan_rules::ScanRules;
+
+// Игра, которая нашлась по новому пути
+#[derive(Debug, Serialize, Clone)]
+pub struct MovedGame {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+}
+
+// Результат пересканирования всех папок библиотеки
+#[derive(Serialize, Clone, Default)]
+pub struct RescanReport {
+    pub added: Vec<GameInfo>,
+    pub disappeared: Vec<GameEntry>,
+    pub moved: Vec<MovedGame>,
+    // Архивы с играми, которые лежат в папках библиотеки нераспакованными
+    pub not_installed: Vec<ArchiveCandidate>,
+    // Папки, которые сейчас недоступны (отключенный диск, сетевая папка).
+    // Игры из них не считаются пропавшими.
+    pub unavailable_roots: Vec<String>,
+}
+
+fn file_name_key(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn parent_name_key(path: &str) -> String {
+    Path::new(path)
+        .parent()
+        .and_then(|parent| parent.file_name())
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+// Для пропавшей игры ищем среди новых ту же игру: совпадает имя исполняемого файла,
+// а при нескольких кандидатах - еще и имя папки игры
+fn find_moved(missing: &GameEntry, added: &[GameInfo], taken: &HashSet<usize>) -> Option<usize> {
+    let candidates: Vec<usize> = added
+        .iter()
+        .enumerate()
+        .filter(|(i, info)| !taken.contains(i) && file_name_key(info.path()) == file_name_key(&missing.path))
+        .map(|(i, _)| i)
+        .collect();
+    if candidates.len() == 1 {
+        return candidates.first().copied();
+    }
+    let parent = parent_name_key(&missing.path);
+    let mut same_parent = candidates
+        .into_iter()
+        .filter(|i| parent_name_key(added[*i].path()) == parent);
+    match (same_parent.next(), same_parent.next()) {
+        (Some(i), None) => Some(i),
+        _ => None,
+    }
+}
+
+// Папки лаунчера, названные по пути игры (свой WINEPREFIX, логи сессий), переезжают вместе с ней.
+// Ошибка здесь не отменяет переноса: игра останется в библиотеке, только без старых логов.
+fn move_game_data(app_data_dir: &Path, from: &str, to: &str) {
+    let dirs = [
+        (runners::prefix_dir(app_data_dir, from, "game"), runners::prefix_dir(app_data_dir, to, "game")),
+        (logs::game_logs_dir(app_data_dir, from), logs::game_logs_dir(app_data_dir, to)),
+    ];
+    for (old_dir, new_dir) in dirs {
+        if !old_dir.is_dir() || new_dir.exists() {
+            continue;
+        }
+        if let Err(e) = std::fs::rename(&old_dir, &new_dir) {
+            eprintln!("Не удалось перенести {:?} в {:?}: {}", old_dir, new_dir, e);
+        }
+    }
+}
+
+fn is_under(path: &str, root: &Path) -> bool {
+    Path::new(path).starts_with(root)
+}
+
+// Обходит все папки библиотеки и сравнивает найденное с таблицей games.
+// apply = true сразу добавляет новые игры и переносит перемещенные на новый путь;
+// пропавшие игры только попадают в отчет, удаляет их пользователь.
+pub fn rescan(app_data_dir: &Path, apply: bool) -> Result<RescanReport, String> {
+    let roots = database::db_get_library_roots()?;
+    let games = database::load_games(app_data_dir, None)?;
+    let known_paths: HashSet<&str> = games.iter().map(|game| game.path.as_str()).collect();
+
+    let mut report = RescanReport::default();
+    let mut scanned_roots: Vec<PathBuf> = Vec::new();
+    let mut found_paths: HashSet<String> = HashSet::new();
+    let mut added_by_path: HashMap<String, GameInfo> = HashMap::new();
+
+    for root in &roots {
+        let root_path = PathBuf::from(&root.path);
+        if !root_path.is_dir() {
+            report.unavailable_roots.push(root.path.clone());
+            continue;
+        }
+        let found = scanner::scan_directory(
+            &root.path,
+            root.deep_search,
+            &ScanRules::load().with_globs(&root.dir_blacklist, &root.file_blacklist),
+        );
+        report.not_installed.extend(found.archives);
+        for info in found.games {
+            found_paths.insert(info.path().to_string());
+            if !known_paths.contains(info.path()) {
+                added_by_path.insert(info.path().to_string(), info);
+            }
+        }
+        scanned_roots.push(root_path);
+    }
+
+    let mut added: Vec<GameInfo> = added_by_path.into_values().collect();
+    added.sort_by(|a, b| a.path().cmp(b.path()));
+
+    // Пропавшими считаем только игры из просканированных папок, файла которых больше нет:
+    // игра могла просто попасть под черный список
+    let disappeared: Vec<&GameEntry> = games
+        .iter()
+        .filter(|game| scanned_roots.iter().any(|root| is_under(&game.path, root)))
+        .filter(|game| !found_paths.contains(&game.path) && !Path::new(&game.path).exists())
+        .collect();
+
+    let mut taken = HashSet::new();
+    for game in disappeared {
+        match find_moved(game, &added, &taken) {
+            Some(index) => {
+                taken.insert(index);
+                report.moved.push(MovedGame {
+                    name: game.name.clone(),
+                    from: game.path.clone(),
+                    to: added[index].path().to_string(),
+                });
+            }
+            None => report.disappeared.push(game.clone()),
+        }
+    }
+    report.added = added
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| !taken.contains(i))
+        .map(|(_, info)| info)
+        .collect();
+
+    if apply {
+        for moved in &report.moved {
+            database::move_game_path(&moved.from, &moved.to)?;
+            move_game_data(app_data_dir, &moved.from, &moved.to);
+        }
+        for info in &report.added {
+            let entry = info.to_game_entry();
+            database::db_add_game(entry.clone())?;
+            if let Err(e) = icons::extract_icon_for_game(app_data_dir, &entry) {
+                eprintln!("Иконка для {} не извлечена: {}", entry.path, e);
+            }
+        }
+    }
+    for root in &roots {
+        if let Some(id) = root.id.filter(|_| Path::new(&root.path).is_dir()) {
+            database::set_library_root_scanned(id)?;
+        }
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn rescan_library(app: AppHandle, apply: bool) -> Result<RescanReport, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    tauri::async_runtime::spawn_blocking(move || rescan(&app_data_dir, apply))
+        .await
+        .map_err(|e| e.to_string())?
+}
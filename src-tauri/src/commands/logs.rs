@@ -0,0 +1,243 @@
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+
+// Сколько последних логов храним для каждой игры
+const MAX_LOGS_PER_GAME: usize = 20;
+// До такого размера лог обрезается (остается хвост). Во время сессии лог может дорасти
+// до удвоенного размера: обрезать после каждой записи было бы слишком дорого.
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+// Сколько отдаем фронтенду по умолчанию
+const DEFAULT_READ_KB: u64 = 64;
+
+// Лог одной сессии игры
+#[derive(Debug, Serialize, Clone)]
+pub struct GameLog {
+    session_id: i64,
+    path: String,
+    size_bytes: u64,
+    modified: String,
+}
+
+// Имя папки читаемое, а хэш полного пути различает пути, которые совпадают после замены
+// символов на "_" ("a_b\game.exe" и "a\b\game.exe")
+pub fn game_logs_dir(app_data_dir: &Path, game_path: &str) -> PathBuf {
+    let safe_game_path = game_path.replace(|c: char| !c.is_alphanumeric(), "_");
+    app_data_dir
+        .join("logs")
+        .join(format!("{}_{:08x}", safe_game_path, path_hash(game_path) as u32))
+}
+
+// FNV-1a: в отличие от DefaultHasher не меняется между версиями Rust, а имена папок должны быть постоянными
+fn path_hash(path: &str) -> u64 {
+    path.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn session_log_path(app_data_dir: &Path, game_path: &str, session_id: i64) -> PathBuf {
+    game_logs_dir(app_data_dir, game_path).join(format!("{}.log", session_id))
+}
+
+// Логи игры с номерами сессий, от новых к старым
+fn session_logs(logs_dir: &Path) -> Vec<(i64, PathBuf)> {
+    let Ok(entries) = fs::read_dir(logs_dir) else {
+        return Vec::new();
+    };
+    let mut logs: Vec<(i64, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("log") {
+                return None;
+            }
+            let session_id = path.file_stem()?.to_str()?.parse().ok()?;
+            Some((session_id, path))
+        })
+        .collect();
+    logs.sort_by_key(|(session_id, _)| std::cmp::Reverse(*session_id));
+    logs
+}
+
+// Создает файл лога для новой сессии и удаляет самые старые логи игры.
+// Вывод игры пишется в него через capture_output.
+pub fn open_session_log(
+    app_data_dir: &Path,
+    game_path: &str,
+    session_id: i64,
+) -> Result<(File, PathBuf), String> {
+    let logs_dir = game_logs_dir(app_data_dir, game_path);
+    fs::create_dir_all(&logs_dir).map_err(|e| e.to_string())?;
+
+    // Оставляем место для нового лога
+    for (_, old_log) in session_logs(&logs_dir).into_iter().skip(MAX_LOGS_PER_GAME - 1) {
+        if let Err(e) = fs::remove_file(&old_log) {
+            eprintln!("Не удалось удалить старый лог {:?}: {}", old_log, e);
+        }
+    }
+
+    let path = session_log_path(app_data_dir, game_path, session_id);
+    let mut file = File::create(&path).map_err(|e| e.to_string())?;
+    writeln!(
+        file,
+        "=== {} | сессия {} | {} ===",
+        game_path,
+        session_id,
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    )
+    .map_err(|e| e.to_string())?;
+    Ok((file, path))
+}
+
+// Читает не больше max_bytes с конца файла
+fn read_tail(path: &Path, max_bytes: u64) -> Result<Vec<u8>, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+    file.seek(SeekFrom::Start(size.saturating_sub(max_bytes)))
+        .map_err(|e| e.to_string())?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer).map_err(|e| e.to_string())?;
+    Ok(buffer)
+}
+
+// Оставляет от лога последние MAX_LOG_BYTES, обычно именно там трейсбек падения.
+// Возвращает новый размер файла.
+fn trim_to_tail(path: &Path) -> Result<u64, String> {
+    let size = fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size <= MAX_LOG_BYTES {
+        return Ok(size);
+    }
+    let tail = read_tail(path, MAX_LOG_BYTES)?;
+    let mut file = File::create(path).map_err(|e| e.to_string())?;
+    writeln!(file, "... (начало лога обрезано, было {} байт)", size).map_err(|e| e.to_string())?;
+    file.write_all(&tail).map_err(|e| e.to_string())?;
+    file.metadata().map(|m| m.len()).map_err(|e| e.to_string())
+}
+
+// Лог сессии, который не растет без предела, даже если игра часами пишет в stdout
+struct CappedLog {
+    file: File,
+    path: PathBuf,
+    size: u64,
+}
+
+impl CappedLog {
+    fn write(&mut self, data: &[u8]) -> Result<(), String> {
+        self.file.write_all(data).map_err(|e| e.to_string())?;
+        self.size += data.len() as u64;
+        if self.size > 2 * MAX_LOG_BYTES {
+            self.trim()?;
+        }
+        Ok(())
+    }
+
+    fn trim(&mut self) -> Result<(), String> {
+        self.file.flush().map_err(|e| e.to_string())?;
+        self.size = trim_to_tail(&self.path)?;
+        self.file = fs::OpenOptions::new()
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+// Перекачивает stdout и stderr игры (Stdio::piped) в лог в отдельных потоках.
+// Pipe закрывается, когда выходят все процессы игры; тогда лог обрезается окончательно.
+pub fn capture_output(child: &mut Child, file: File, path: PathBuf) {
+    // Ошибка здесь не должна оставить pipe непрочитанным: игра зависнет, когда он заполнится
+    let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let log = Arc::new(Mutex::new(CappedLog { file, path, size }));
+    let streams: Vec<Box<dyn Read + Send>> = [
+        child.stdout.take().map(|s| Box::new(s) as Box<dyn Read + Send>),
+        child.stderr.take().map(|s| Box::new(s) as Box<dyn Read + Send>),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let open_streams = Arc::new(AtomicUsize::new(streams.len()));
+    for mut stream in streams {
+        let log = Arc::clone(&log);
+        let open_streams = Arc::clone(&open_streams);
+        std::thread::spawn(move || {
+            let mut chunk = [0u8; 8192];
+            let mut failed = false;
+            while let Ok(n) = stream.read(&mut chunk) {
+                if n == 0 {
+                    break;
+                }
+                // После ошибки вывод только вычитываем, иначе игра встанет на заполненном pipe
+                if failed {
+                    continue;
+                }
+                if let Err(e) = log.lock().unwrap().write(&chunk[..n]) {
+                    eprintln!("Не удалось записать лог сессии: {}", e);
+                    failed = true;
+                }
+            }
+            if open_streams.fetch_sub(1, Ordering::SeqCst) == 1 {
+                let mut log = log.lock().unwrap();
+                if let Err(e) = log.trim() {
+                    eprintln!("Не удалось обрезать лог {:?}: {}", log.path, e);
+                }
+            }
+        });
+    }
+}
+
+#[tauri::command]
+pub fn list_game_logs(app: AppHandle, game_path: String) -> Result<Vec<GameLog>, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let logs = session_logs(&game_logs_dir(&app_data_dir, &game_path))
+        .into_iter()
+        .filter_map(|(session_id, path)| {
+            let metadata = fs::metadata(&path).ok()?;
+            let modified: chrono::DateTime<chrono::Local> = metadata.modified().ok()?.into();
+            Some(GameLog {
+                session_id,
+                path: path.to_string_lossy().into_owned(),
+                size_bytes: metadata.len(),
+                modified: modified.format("%Y-%m-%d %H:%M:%S").to_string(),
+            })
+        })
+        .collect();
+    Ok(logs)
+}
+
+// Последние max_kb килобайт лога сессии
+#[tauri::command]
+pub fn read_game_log(
+    app: AppHandle,
+    game_path: String,
+    session_id: i64,
+    max_kb: Option<u64>,
+) -> Result<String, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let path = session_log_path(&app_data_dir, &game_path, session_id);
+    if !path.is_file() {
+        return Err(format!("Лог сессии {} не найден", session_id));
+    }
+    let max_bytes = max_kb.unwrap_or(DEFAULT_READ_KB).saturating_mul(1024).min(MAX_LOG_BYTES);
+    let tail = read_tail(&path, max_bytes)?;
+    Ok(String::from_utf8_lossy(&tail).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_dirs_differ_for_paths_equal_after_sanitizing() {
+        let data = Path::new("/data");
+        assert_ne!(
+            game_logs_dir(data, r"C:\Games\a_b\game.exe"),
+            game_logs_dir(data, r"C:\Games\a\b\game.exe")
+        );
+        assert_eq!(game_logs_dir(data, "/games/a/game"), game_logs_dir(data, "/games/a/game"));
+    }
+}
@@ -1,2 +1,12 @@
 pub mod scanner;
-pub mod backups;
\ No newline at end of file
+pub mod backups;
+pub mod launcher;
+pub mod runners;
+pub mod hooks;
+pub mod shortcuts;
+pub mod logs;
+pub mod crashes;
+pub mod icons;
+pub mod library;
+pub mod watcher;
+pub mod install;
\ No newline at end of file
@@ -0,0 +1,145 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::database::Runner;
+
+// Установка Wine/Proton, найденная в системе, но еще не добавленная в базу
+#[derive(Debug, Serialize, Clone)]
+pub struct DetectedRunner {
+    name: String,
+    kind: String,
+    path: String,
+}
+
+// Как запустить Windows-программу через раннер
+pub struct RunnerInvocation {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub env_vars: Vec<(String, String)>,
+}
+
+// Где обычно лежит Steam (и вместе с ним Proton)
+fn steam_roots() -> Vec<PathBuf> {
+    let Some(home) = env::var_os("HOME").map(PathBuf::from) else {
+        return Vec::new();
+    };
+    [
+        ".steam/steam",
+        ".steam/root",
+        ".local/share/Steam",
+        ".var/app/com.valvesoftware.Steam/data/Steam",
+    ]
+    .iter()
+    .map(|dir| home.join(dir))
+    .filter(|dir| dir.is_dir())
+    .collect()
+}
+
+// Подпапки `dir`, в которых лежит файл `relative_binary`
+fn find_in_subdirs(dir: &Path, relative_binary: &str) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| {
+            let binary = entry.path().join(relative_binary);
+            binary
+                .is_file()
+                .then(|| (entry.file_name().to_string_lossy().into_owned(), binary))
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub async fn detect_runners() -> Result<Vec<DetectedRunner>, String> {
+    let mut found: Vec<DetectedRunner> = Vec::new();
+    let mut seen = HashSet::new();
+    let mut add = |name: String, kind: &str, path: PathBuf| {
+        // Одна и та же установка может быть доступна по нескольким путям (симлинки Steam)
+        let key = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        if seen.insert(key) {
+            found.push(DetectedRunner {
+                name,
+                kind: kind.to_string(),
+                path: path.to_string_lossy().into_owned(),
+            });
+        }
+    };
+
+    // Системный Wine из PATH
+    if let Some(paths) = env::var_os("PATH") {
+        for dir in env::split_paths(&paths) {
+            let wine = dir.join("wine");
+            if wine.is_file() {
+                add("Wine (system)".to_string(), "wine", wine);
+            }
+        }
+    }
+
+    // Сборки Wine, установленные через Lutris
+    if let Some(home) = env::var_os("HOME").map(PathBuf::from) {
+        let lutris_dir = home.join(".local/share/lutris/runners/wine");
+        for (name, path) in find_in_subdirs(&lutris_dir, "bin/wine") {
+            add(name, "wine", path);
+        }
+    }
+
+    // Proton из Steam и сторонние сборки (GE-Proton) в compatibilitytools.d
+    for root in steam_roots() {
+        for dir in ["steamapps/common", "compatibilitytools.d"] {
+            for (name, path) in find_in_subdirs(&root.join(dir), "proton") {
+                add(name, "proton", path);
+            }
+        }
+    }
+
+    Ok(found)
+}
+
+// Папка WINEPREFIX для игры: общая для всех игр или своя для каждой
+pub fn prefix_dir(app_data_dir: &Path, game_path: &str, prefix_mode: &str) -> PathBuf {
+    let prefixes_dir = app_data_dir.join("prefixes");
+    if prefix_mode == "game" {
+        let safe_game_path = game_path.replace(|c: char| !c.is_alphanumeric(), "_");
+        prefixes_dir.join(safe_game_path)
+    } else {
+        prefixes_dir.join("shared")
+    }
+}
+
+// windows_command - программа и ее аргументы так, как их запускали бы в Windows,
+// например ["game.exe"] или ["cmd", "/c", "start.bat"]
+pub fn runner_invocation(
+    runner: &Runner,
+    prefix_dir: &Path,
+    windows_command: Vec<String>,
+) -> RunnerInvocation {
+    let prefix = prefix_dir.to_string_lossy().into_owned();
+
+    if runner.kind == "proton" {
+        // Proton сам создает префикс в pfx/ внутри STEAM_COMPAT_DATA_PATH
+        let client_path = steam_roots()
+            .into_iter()
+            .next()
+            .map(|root| root.to_string_lossy().into_owned())
+            .unwrap_or_else(|| prefix.clone());
+        RunnerInvocation {
+            program: PathBuf::from(&runner.path),
+            args: std::iter::once("run".to_string()).chain(windows_command).collect(),
+            env_vars: vec![
+                ("STEAM_COMPAT_DATA_PATH".to_string(), prefix),
+                ("STEAM_COMPAT_CLIENT_INSTALL_PATH".to_string(), client_path),
+            ],
+        }
+    } else {
+        RunnerInvocation {
+            program: PathBuf::from(&runner.path),
+            args: windows_command,
+            env_vars: vec![("WINEPREFIX".to_string(), prefix)],
+        }
+    }
+}
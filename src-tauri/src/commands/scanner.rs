@@ -1,6 +1,51 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
 use serde::Serialize;
-use walkdir::{DirEntry, WalkDir};
+use tauri::ipc::Channel;
+
+use crate::archives::{self, ArchiveCandidate};
+use crate::database::GameEntry;
+use crate::engines::{self, DirListing};
+use crate::renpy::{self, RenpyMetadata};
+use crate::scan_rules::{self, ScanRules};
+
+// Флаги отмены запущенных сканирований по их id
+static ACTIVE_SCANS: Lazy<Mutex<HashMap<u64, Arc<AtomicBool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_SCAN_ID: AtomicU64 = AtomicU64::new(1);
+
+// Как часто отправлять прогресс: на каждую папку событий было бы слишком много
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ScanProgress {
+    dirs_visited: u64,
+    // Найденные исполняемые файлы до отсева дубликатов
+    candidates: u64,
+    current_path: String,
+}
+
+// Результат сканирования: установленные игры и архивы с играми, которые еще не распакованы
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ScanOutcome {
+    pub games: Vec<GameInfo>,
+    pub archives: Vec<ArchiveCandidate>,
+    pub cancelled: bool,
+}
+
+// События сканирования для фронтенда. Finished приходит всегда, в том числе после отмены:
+// тогда в нем то, что успели найти.
+#[derive(Serialize, Clone)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum ScanEvent {
+    Progress(ScanProgress),
+    Finished(ScanOutcome),
+}
 
 #[derive(Debug, Serialize, Clone)]
 pub struct GameInfo {
@@ -8,83 +53,400 @@ pub struct GameInfo {
     path: String,
     game_type: String,
     version: Option<String>,
+    // Движок по структуре папки игры, см. engines::detect_engine
+    engine: Option<String>,
+    // Название из config.name, если его удалось прочитать (иначе имя берется из name)
+    title: Option<String>,
+    // Откуда взяты название и версия: "file", "path" или "options.rpy"
+    name_source: String,
+    version_source: Option<String>,
+    save_directory: Option<String>,
+    build_name: Option<String>,
+    engine_version: Option<String>,
+}
+
+impl GameInfo {
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    // Запись для библиотеки: имя без расширения, как его добавляет фронтенд
+    pub fn to_game_entry(&self) -> GameEntry {
+        let name = self.title.as_deref().unwrap_or_else(|| {
+            self.name
+                .rsplit_once('.')
+                .map(|(stem, _)| stem)
+                .unwrap_or(&self.name)
+        });
+        GameEntry {
+            path: self.path.clone(),
+            name: name.to_string(),
+            game_type: self.game_type.clone(),
+            play_time_seconds: 0,
+            icon_path: None,
+            icon_url: None,
+            description: None,
+            version: self.version.clone(),
+            last_played: None,
+            rating: 0,
+            is_hidden: false,
+            completion_percent: 0,
+            engine: self.engine.clone(),
+            name_source: Some(self.name_source.clone()),
+            version_source: self.version_source.clone(),
+            save_directory: self.save_directory.clone(),
+            build_name: self.build_name.clone(),
+            engine_version: self.engine_version.clone(),
+            title_key: None,
+            is_current_version: true,
+        }
+    }
 }
 
-// Вспомогательная функция для проверки, находится ли запись в черном списке
-pub fn is_in_blacklist(entry: &DirEntry, dir_blacklist: &[String]) -> bool {
-    if let Some(file_name) = entry.file_name().to_str() {
-        return dir_blacklist.contains(&file_name.to_lowercase());
+// Папки с ресурсами, в которых у игры на этом движке не бывает исполняемых файлов.
+// Как только движок папки определен, в них не заходим: там основная масса файлов.
+fn is_asset_dir(engine: &str, dir_name: &str) -> bool {
+    match engine {
+        "renpy" => matches!(dir_name, "game" | "renpy" | "lib"),
+        "unity" => dir_name.ends_with("_data") || dir_name == "monobleedingedge",
+        "rpgmaker" => matches!(
+            dir_name,
+            "www" | "js" | "data" | "img" | "audio" | "movies" | "locales" | "swiftshader"
+        ),
+        _ => false,
     }
-    false
 }
 
 #[tauri::command]
 pub async fn scan_for_games_recursively(
     path: String,
     deep_search: bool,
-    dir_blacklist: Vec<String>,
-    file_blacklist: Vec<String>,
 ) -> Result<Vec<GameInfo>, String> {
-    let mut potential_games_by_dir: HashMap<String, Vec<GameInfo>> = HashMap::new();
-    let executable_extensions = ["exe", "py", "sh", "bat", "cmd"];
-
-    let max_depth = if deep_search { usize::MAX } else { 3 };
-
-    let walker = WalkDir::new(path)
-        .max_depth(max_depth)
-        .into_iter()
-        .filter_entry(|e| !is_in_blacklist(e, &dir_blacklist));
-
-    for entry in walker.filter_map(|e| e.ok()) {
-        if entry.file_type().is_file() {
-            if let Some(extension) = entry.path().extension().and_then(|s| s.to_str()) {
-                if executable_extensions.contains(&extension.to_lowercase().as_str()) {
-                    let file_name = entry.file_name().to_string_lossy().to_string();
-
-                    if file_blacklist.contains(&file_name.to_lowercase()) {
-                        continue;
-                    }
-
-                    let full_path = entry.path().to_string_lossy().to_string();
-                    if let Some(parent) = entry.path().parent() {
-                        let parent_dir = parent.to_string_lossy().to_string();
-
-                        let game_info = GameInfo {
-                            name: file_name,
-                            path: full_path.clone(), // Клонируем, так как full_path нужен для версии
-                            game_type: extension.to_lowercase(),
-                            version: extract_version_from_path(&full_path), // <-- ИСПОЛЬЗУЕМ НАШУ НОВУЮ ФУНКЦИЮ
-                        };
-
-                        potential_games_by_dir
-                            .entry(parent_dir)
-                            .or_default()
-                            .push(game_info);
-                    }
+    Ok(scan_directory(&path, deep_search, &ScanRules::load()).games)
+}
+
+// Сканирование в фоне: сразу возвращает id (для cancel_scan),
+// а прогресс и результат приходят через on_event
+#[tauri::command]
+pub fn start_scan(
+    path: String,
+    deep_search: bool,
+    on_event: Channel<ScanEvent>,
+) -> u64 {
+    let scan_id = NEXT_SCAN_ID.fetch_add(1, Ordering::SeqCst);
+    let cancel = Arc::new(AtomicBool::new(false));
+    ACTIVE_SCANS.lock().unwrap().insert(scan_id, cancel.clone());
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut guard = ScanGuard {
+            scan_id,
+            on_event,
+            outcome: None,
+        };
+        let outcome = scan_directory_with_progress(
+            &path,
+            deep_search,
+            &ScanRules::load(),
+            &cancel,
+            &|progress| {
+                let _ = guard.on_event.send(ScanEvent::Progress(progress.clone()));
+            },
+        );
+        guard.outcome = Some(outcome);
+    });
+    scan_id
+}
+
+// Завершает сканирование при выходе из потока, в том числе по панике в обходе:
+// Finished приходит всегда, и фронтенд не ждет результата вечно
+struct ScanGuard {
+    scan_id: u64,
+    on_event: Channel<ScanEvent>,
+    outcome: Option<ScanOutcome>,
+}
+
+impl Drop for ScanGuard {
+    fn drop(&mut self) {
+        ACTIVE_SCANS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.scan_id);
+        let outcome = self.outcome.take().unwrap_or_else(|| {
+            eprintln!("Сканирование {} прервано ошибкой", self.scan_id);
+            ScanOutcome {
+                cancelled: true,
+                ..ScanOutcome::default()
+            }
+        });
+        let _ = self.on_event.send(ScanEvent::Finished(outcome));
+    }
+}
+
+// Возвращает false, если сканирование уже закончилось
+#[tauri::command]
+pub fn cancel_scan(scan_id: u64) -> bool {
+    match ACTIVE_SCANS.lock().unwrap().get(&scan_id) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+// Синхронная часть сканирования, общая для команды и CLI
+pub fn scan_directory(path: &str, deep_search: bool, rules: &ScanRules) -> ScanOutcome {
+    let never_cancelled = AtomicBool::new(false);
+    scan_directory_with_progress(path, deep_search, rules, &never_cancelled, &|_| {})
+}
+
+// Сканирование с отчетом о прогрессе. Если cancel выставлен, обход прерывается
+// и возвращается то, что найдено к этому моменту.
+pub fn scan_directory_with_progress(
+    path: &str,
+    deep_search: bool,
+    rules: &ScanRules,
+    cancel: &AtomicBool,
+    on_progress: &(dyn Fn(&ScanProgress) + Sync),
+) -> ScanOutcome {
+    walk_directory(path, deep_search, rules, true, cancel, on_progress)
+}
+
+// Глубина обхода считается как в WalkDir: у файлов прямо в выбранной папке она равна 1.
+// Игры ищутся в папках с глубиной меньше этой.
+pub fn max_depth(deep_search: bool) -> usize {
+    if deep_search { usize::MAX } else { 3 }
+}
+
+// Параллельный обход: каждая папка читается отдельной задачей rayon.
+// prune_assets = false отключает отсечение папок с ресурсами (только для замера в тестах).
+fn walk_directory(
+    path: &str,
+    deep_search: bool,
+    rules: &ScanRules,
+    prune_assets: bool,
+    cancel: &AtomicBool,
+    on_progress: &(dyn Fn(&ScanProgress) + Sync),
+) -> ScanOutcome {
+    let walker = Walker {
+        rules,
+        max_depth: max_depth(deep_search),
+        prune_assets,
+        cancel,
+        on_progress,
+        dirs_visited: AtomicU64::new(0),
+        candidates: AtomicU64::new(0),
+        last_report: Mutex::new(Instant::now()),
+        games: Mutex::new(Vec::new()),
+        archives: Mutex::new(Vec::new()),
+    };
+    rayon::scope(|scope| walker.visit(scope, PathBuf::from(path), 0, Arc::new(Vec::new())));
+
+    // Архивы смотрим после обхода: для .7z и .rar это запуск внешнего 7z
+    let archive_paths = std::mem::take(&mut *walker.archives.lock().unwrap());
+    let mut archives: Vec<ArchiveCandidate> = archive_paths
+        .par_iter()
+        .filter(|_| !cancel.load(Ordering::Relaxed))
+        .filter_map(|path| match archives::inspect(path) {
+            Ok(candidate) => candidate,
+            Err(e) => {
+                eprintln!("Не удалось прочитать архив {:?}: {}", path, e);
+                None
+            }
+        })
+        .filter(|candidate| !archives::is_extracted_next_to(candidate))
+        .collect();
+    archives.sort_by(|a, b| a.path.cmp(&b.path));
+
+    on_progress(&walker.progress(String::new()));
+    let mut games = walker.games.into_inner().unwrap();
+    games.sort_by(|a, b| a.path.cmp(&b.path));
+    ScanOutcome {
+        games,
+        archives,
+        cancelled: cancel.load(Ordering::Relaxed),
+    }
+}
+
+// Правила из .rvnignore текущей папки и всех ее родителей
+type IgnoreChain = Arc<Vec<Arc<ScanRules>>>;
+
+struct Walker<'a> {
+    rules: &'a ScanRules,
+    max_depth: usize,
+    prune_assets: bool,
+    cancel: &'a AtomicBool,
+    on_progress: &'a (dyn Fn(&ScanProgress) + Sync),
+    dirs_visited: AtomicU64,
+    candidates: AtomicU64,
+    last_report: Mutex<Instant>,
+    games: Mutex<Vec<GameInfo>>,
+    archives: Mutex<Vec<PathBuf>>,
+}
+
+impl<'a> Walker<'a> {
+    fn progress(&self, current_path: String) -> ScanProgress {
+        ScanProgress {
+            dirs_visited: self.dirs_visited.load(Ordering::Relaxed),
+            candidates: self.candidates.load(Ordering::Relaxed),
+            current_path,
+        }
+    }
+
+    // Отчитываемся не чаще PROGRESS_INTERVAL; если отчет уже отправляет другой поток, пропускаем
+    fn report(&self, dir: &Path) {
+        let Ok(mut last_report) = self.last_report.try_lock() else {
+            return;
+        };
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            (self.on_progress)(&self.progress(dir.to_string_lossy().to_string()));
+            *last_report = Instant::now();
+        }
+    }
+
+    fn excludes(&self, ignores: &IgnoreChain, name: &str, is_dir: bool) -> bool {
+        self.rules.excludes(name, is_dir) || ignores.iter().any(|rules| rules.excludes(name, is_dir))
+    }
+
+    // depth - глубина самой папки dir (у выбранной папки 0)
+    fn visit<'s>(&'s self, scope: &rayon::Scope<'s>, dir: PathBuf, depth: usize, ignores: IgnoreChain) {
+        if self.cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        let Ok(read_dir) = std::fs::read_dir(&dir) else {
+            return;
+        };
+        // (имя, путь, это папка)
+        let entries: Vec<(String, PathBuf, bool)> = read_dir
+            .filter_map(|e| e.ok())
+            .filter_map(|entry| {
+                let file_type = entry.file_type().ok()?;
+                // Ссылки не раскрываем, как и WalkDir по умолчанию
+                if !file_type.is_dir() && !file_type.is_file() {
+                    return None;
                 }
+                let name = entry.file_name().to_string_lossy().to_string();
+                Some((name, entry.path(), file_type.is_dir()))
+            })
+            .collect();
+        self.dirs_visited.fetch_add(1, Ordering::Relaxed);
+        self.report(&dir);
+
+        // Движок определяем по полному списку: у Ren'Py папки game/ и renpy/ есть в черном списке
+        let listing = DirListing::from_entries(
+            entries.iter().map(|(name, _, is_dir)| (name.clone(), *is_dir)),
+        );
+        let engine = engines::detect_engine(&listing);
+
+        let has_ignore_file = entries
+            .iter()
+            .any(|(name, _, is_dir)| !is_dir && name == scan_rules::IGNORE_FILE_NAME);
+        let ignores = match has_ignore_file.then(|| ScanRules::read_ignore_file(&dir)).flatten() {
+            Some(local) => {
+                let mut chain = ignores.as_ref().clone();
+                chain.push(Arc::new(local));
+                Arc::new(chain)
+            }
+            None => ignores,
+        };
+        let entries: Vec<(String, PathBuf, bool)> = entries
+            .into_iter()
+            .filter(|(name, _, is_dir)| !self.excludes(&ignores, name, *is_dir))
+            .collect();
+
+        if depth < self.max_depth {
+            self.collect_games(&dir, engine, &entries);
+            let mut archives = self.archives.lock().unwrap();
+            archives.extend(
+                entries
+                    .iter()
+                    .filter(|(_, path, is_dir)| !is_dir && archives::is_archive(path))
+                    .map(|(_, path, _)| path.clone()),
+            );
+        }
+        if depth + 1 >= self.max_depth {
+            return;
+        }
+        for (name, path, is_dir) in entries {
+            if !is_dir {
+                continue;
+            }
+            let lowercase = name.to_lowercase();
+            if self.prune_assets && engine.is_some_and(|engine| is_asset_dir(engine, &lowercase)) {
+                continue;
             }
+            let ignores = ignores.clone();
+            scope.spawn(move |scope| self.visit(scope, path, depth + 1, ignores));
         }
     }
 
-    // --- Фаза 2: Фильтрация дубликатов ---
-    let mut final_games = Vec::new();
-    for (_dir, games_in_dir) in potential_games_by_dir {
+    // Исполняемые файлы одной папки. Дубликаты (game.exe, game.sh, game-32.exe)
+    // отсеиваются сразу, так как все они лежат рядом.
+    fn collect_games(&self, dir: &Path, engine: Option<&str>, entries: &[(String, PathBuf, bool)]) {
+        let executable_extensions = ["exe", "py", "sh", "bat", "cmd"];
+        let executables: Vec<(&String, &PathBuf, String)> = entries
+            .iter()
+            .filter(|(_, _, is_dir)| !is_dir)
+            .filter_map(|(name, path, _)| {
+                let extension = path.extension()?.to_str()?.to_lowercase();
+                executable_extensions
+                    .contains(&extension.as_str())
+                    .then_some((name, path, extension))
+            })
+            .collect();
+        if executables.is_empty() {
+            return;
+        }
+        self.candidates.fetch_add(executables.len() as u64, Ordering::Relaxed);
+
+        // Метаданные читаем один раз на папку: в ней обычно лежит несколько исполняемых файлов
+        let renpy = if engine == Some("renpy") {
+            renpy::read_metadata(dir)
+        } else {
+            RenpyMetadata::default()
+        };
+
         let mut game_groups: HashMap<String, Vec<GameInfo>> = HashMap::new();
+        for (file_name, path, extension) in executables {
+            let full_path = path.to_string_lossy().to_string();
+            // Версия из options.rpy надежнее той, что угадана по имени папки
+            let (version, version_source) = match renpy.version.clone() {
+                Some(version) => (Some(version), Some("options.rpy".to_string())),
+                None => {
+                    let version = extract_version_from_path(&full_path);
+                    let source = version.as_ref().map(|_| "path".to_string());
+                    (version, source)
+                }
+            };
+            let name_source = if renpy.name.is_some() { "options.rpy" } else { "file" };
 
-        for game in games_in_dir {
-            let base_name = game
+            let game_info = GameInfo {
+                name: file_name.clone(),
+                path: full_path,
+                game_type: extension,
+                version,
+                engine: engine.map(String::from),
+                title: renpy.name.clone(),
+                name_source: name_source.to_string(),
+                version_source,
+                save_directory: renpy.save_directory.clone(),
+                build_name: renpy.build_name.clone(),
+                engine_version: renpy.sdk_version.clone(),
+            };
+
+            let base_name = game_info
                 .name
                 .rsplitn(2, '.')
                 .nth(1)
-                .unwrap_or(&game.name)
+                .unwrap_or(&game_info.name)
                 .trim_end_matches("-32")
                 .to_lowercase();
-
-            game_groups.entry(base_name).or_default().push(game);
+            game_groups.entry(base_name).or_default().push(game_info);
         }
 
+        let mut games = self.games.lock().unwrap();
         for (_base_name, group) in game_groups {
-            let best_game = group.iter().min_by_key(|g| match g.game_type.as_str() {
+            let best_game = group.into_iter().min_by_key(|g| match g.game_type.as_str() {
                 "exe" if !g.name.to_lowercase().contains("-32") => 0,
                 "exe" => 1,
                 "sh" => 2,
@@ -92,66 +454,62 @@ pub async fn scan_for_games_recursively(
                 "py" => 4,
                 _ => 5,
             });
-
             if let Some(game) = best_game {
-                final_games.push(game.clone());
+                games.push(game);
             }
         }
     }
-
-    Ok(final_games)
 }
 
+// Функция, которая будет анализировать строку (имя файла, папки или архива)
+pub fn find_version_in_name(name: &str) -> Option<String> {
+    // Разделители, по которым будем бить строку
+    let delimiters = &['-', '_', ' '];
+    let mut version_parts = Vec::new();
+    let mut found_version_start = false;
 
-fn extract_version_from_path(path_str: &str) -> Option<String> {
-    // Функция, которая будет анализировать строку (имя файла или папки)
-    fn find_version_in_name(name: &str) -> Option<String> {
-        // Разделители, по которым будем бить строку
-        let delimiters = &['-', '_', ' '];
-        let mut version_parts = Vec::new();
-        let mut found_version_start = false;
-
-        // Разбиваем имя на компоненты
-        for part in name.split(delimiters) {
-            // Если в компоненте есть хотя бы одна цифра, считаем, что это часть версии
-            if part.chars().any(|c| c.is_digit(10)) {
-                // Игнорируем слишком короткие части, которые могут быть просто артиклями (например, "a1")
-                // или чисто цифровые, если это не первая часть версии (чтобы не захватить "-32" отдельно)
-                if part.len() > 1 || found_version_start {
-                    version_parts.push(part);
-                    found_version_start = true;
-                }
-            } else {
-                // Если мы уже начали собирать версию и встретили компонент без цифр,
-                // скорее всего, версия закончилась (например, ...-pc-Rus).
-                // Но мы продолжим, чтобы захватить суффиксы типа "pc" или "rus"
-                if found_version_start {
-                    version_parts.push(part);
-                }
+    // Разбиваем имя на компоненты
+    for part in name.split(delimiters) {
+        // Если в компоненте есть хотя бы одна цифра, считаем, что это часть версии
+        if part.chars().any(|c| c.is_digit(10)) {
+            // Игнорируем слишком короткие части, которые могут быть просто артиклями (например, "a1")
+            // или чисто цифровые, если это не первая часть версии (чтобы не захватить "-32" отдельно)
+            if part.len() > 1 || found_version_start {
+                version_parts.push(part);
+                found_version_start = true;
             }
-        }
-
-        if !version_parts.is_empty() {
-            // "Склеиваем" найденные части обратно в одну строку, используя дефис
-            let version_string = version_parts.join("-");
-            // Убираем потенциальный мусор и слишком общие суффиксы
-            let cleaned_version = version_string
-                .trim_end_matches("-pc")
-                .trim_end_matches("-rus")
-                .trim_end_matches("-fix")
-                .trim_end_matches("-1080p")
-                .trim()
-                .to_string();
-
-            // Если после очистки что-то осталось, возвращаем
-            if !cleaned_version.is_empty() {
-                return Some(cleaned_version);
+        } else {
+            // Если мы уже начали собирать версию и встретили компонент без цифр,
+            // скорее всего, версия закончилась (например, ...-pc-Rus).
+            // Но мы продолжим, чтобы захватить суффиксы типа "pc" или "rus"
+            if found_version_start {
+                version_parts.push(part);
             }
         }
+    }
+
+    if !version_parts.is_empty() {
+        // "Склеиваем" найденные части обратно в одну строку, используя дефис
+        let version_string = version_parts.join("-");
+        // Убираем потенциальный мусор и слишком общие суффиксы
+        let cleaned_version = version_string
+            .trim_end_matches("-pc")
+            .trim_end_matches("-rus")
+            .trim_end_matches("-fix")
+            .trim_end_matches("-1080p")
+            .trim()
+            .to_string();
 
-        None
+        // Если после очистки что-то осталось, возвращаем
+        if !cleaned_version.is_empty() {
+            return Some(cleaned_version);
+        }
     }
 
+    None
+}
+
+fn extract_version_from_path(path_str: &str) -> Option<String> {
     // Сначала ищем в имени родительской папки
     if let Some(parent) = std::path::Path::new(path_str).parent() {
         if let Some(dir_name) = parent.file_name().and_then(|s| s.to_str()) {
@@ -173,3 +531,184 @@ fn extract_version_from_path(path_str: &str) -> Option<String> {
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use std::fs;
+    use walkdir::WalkDir;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rvnlauncher-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn touch(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn touch_many(dir: &Path, prefix: &str, extension: &str, count: usize) {
+        fs::create_dir_all(dir).unwrap();
+        for i in 0..count {
+            fs::write(dir.join(format!("{}{}.{}", prefix, i, extension)), "").unwrap();
+        }
+    }
+
+    // Библиотека из Ren'Py, Unity и RPG Maker игр по assets файлов ресурсов у каждой,
+    // разложенная по подпапкам-категориям, как в настоящих библиотеках
+    fn generate_library(root: &Path, games: usize, assets: usize) {
+        for i in 0..games {
+            let category = root.join(format!("category-{}", i % 10));
+            match i % 3 {
+                0 => {
+                    let game = category.join(format!("Novel{}-0.{}-pc", i, i % 7 + 1));
+                    touch(&game.join(format!("Novel{}.exe", i)), "");
+                    touch(&game.join(format!("Novel{}.sh", i)), "");
+                    touch(&game.join(format!("Novel{}.py", i)), "");
+                    touch(
+                        &game.join("game").join("options.rpy"),
+                        &format!("define config.name = _(\"Novel {}\")\n", i),
+                    );
+                    touch_many(&game.join("game").join("images"), "bg", "webp", assets * 3 / 4);
+                    touch_many(&game.join("game").join("audio"), "track", "ogg", assets / 4);
+                    touch_many(&game.join("renpy").join("common"), "module", "rpy", 50);
+                    let lib = game.join("lib").join("py3-windows-x86_64");
+                    touch(&lib.join("python.exe"), "");
+                    touch(&lib.join("pythonw.exe"), "");
+                }
+                1 => {
+                    let game = category.join(format!("Unity{}-v1.{}", i, i % 5));
+                    touch(&game.join(format!("Unity{}.exe", i)), "");
+                    touch(&game.join("UnityPlayer.dll"), "");
+                    let data = game.join(format!("Unity{}_Data", i));
+                    touch_many(&data.join("StreamingAssets"), "bundle", "bundle", assets / 2);
+                    touch_many(&data.join("Managed"), "Assembly", "dll", assets / 4);
+                    touch_many(&data.join("Plugins").join("x86_64"), "plugin", "dll", assets / 4);
+                }
+                _ => {
+                    let game = category.join(format!("Maker{}", i));
+                    touch(&game.join("Game.exe"), "");
+                    touch(&game.join("package.json"), "{}");
+                    let www = game.join("www");
+                    touch_many(&www.join("img").join("pictures"), "pic", "png", assets * 3 / 4);
+                    touch_many(&www.join("audio").join("bgm"), "bgm", "ogg", assets / 4);
+                    touch_many(&www.join("js"), "plugin", "js", 20);
+                }
+            }
+        }
+    }
+
+    // Последовательный обход через WalkDir, как сканер работал до параллельного:
+    // без отсечения папок ресурсов, дубликаты отсеиваются по папке
+    fn walkdir_scan(root: &Path, deep_search: bool, rules: &ScanRules) -> BTreeSet<(String, Option<String>)> {
+        let max_depth = if deep_search { usize::MAX } else { 3 };
+        let mut by_dir: HashMap<PathBuf, Vec<(String, String)>> = HashMap::new();
+        let walker = WalkDir::new(root).max_depth(max_depth).into_iter().filter_entry(|entry| {
+            entry.depth() == 0
+                || !rules.excludes(&entry.file_name().to_string_lossy(), entry.file_type().is_dir())
+        });
+        for entry in walker.filter_map(|e| e.ok()).filter(|e| e.file_type().is_file()) {
+            let Some(extension) = entry.path().extension().map(|e| e.to_string_lossy().to_lowercase()) else {
+                continue;
+            };
+            if ["exe", "py", "sh", "bat", "cmd"].contains(&extension.as_str()) {
+                by_dir
+                    .entry(entry.path().parent().unwrap().to_path_buf())
+                    .or_default()
+                    .push((entry.file_name().to_string_lossy().to_string(), extension));
+            }
+        }
+
+        let mut games = BTreeSet::new();
+        for (dir, executables) in by_dir {
+            let engine = engines::detect_engine(&DirListing::from_dir(&dir)).map(String::from);
+            let mut groups: HashMap<String, Vec<(String, String)>> = HashMap::new();
+            for (name, extension) in executables {
+                let base_name = name.rsplitn(2, '.').nth(1).unwrap_or(&name).trim_end_matches("-32").to_lowercase();
+                groups.entry(base_name).or_default().push((name, extension));
+            }
+            for group in groups.into_values() {
+                let (name, _) = group
+                    .into_iter()
+                    .min_by_key(|(name, extension)| match extension.as_str() {
+                        "exe" if !name.to_lowercase().contains("-32") => 0,
+                        "exe" => 1,
+                        "sh" => 2,
+                        "bat" | "cmd" => 3,
+                        _ => 4,
+                    })
+                    .unwrap();
+                games.insert((dir.join(name).to_string_lossy().to_string(), engine.clone()));
+            }
+        }
+        games
+    }
+
+    #[test]
+    fn parallel_walk_matches_walkdir() {
+        let root = temp_dir("walk");
+        generate_library(&root, 9, 5);
+        // Игра глубже трех уровней: находится только глубоким поиском
+        touch(&root.join("category-0").join("nested").join("Deep-1.0").join("Deep.exe"), "");
+        touch(&root.join("category-1").join("Game-32.exe"), "");
+        touch(&root.join("category-1").join("Game.exe"), "");
+
+        let rules = ScanRules::builtin();
+        let never_cancelled = AtomicBool::new(false);
+        for deep_search in [false, true] {
+            let found = walk_directory(&root.to_string_lossy(), deep_search, &rules, true, &never_cancelled, &|_| {});
+            let parallel: BTreeSet<(String, Option<String>)> =
+                found.games.into_iter().map(|game| (game.path, game.engine)).collect();
+            assert_eq!(parallel, walkdir_scan(&root, deep_search, &rules), "deep_search = {}", deep_search);
+            assert_eq!(parallel.iter().any(|(path, _)| path.ends_with("Deep.exe")), deep_search);
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    // Замер скорости: cargo test bench_scan -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_scan() {
+        const RUNS: usize = 3;
+        let root = temp_dir("bench");
+        generate_library(&root, 300, 400);
+        let path = root.to_string_lossy().to_string();
+        let rules = ScanRules::builtin();
+        let cancel = AtomicBool::new(false);
+
+        let measure = |threads: usize, prune_assets: bool| {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            let mut best = Duration::MAX;
+            let mut games = 0;
+            for _ in 0..RUNS {
+                let started = Instant::now();
+                let found = pool.install(|| walk_directory(&path, true, &rules, prune_assets, &cancel, &|_| {}));
+                best = best.min(started.elapsed());
+                games = found.games.len();
+            }
+            (best, games)
+        };
+        // Прогрев: первый обход заполняет кэш ФС и не должен влиять на сравнение
+        measure(0, true);
+
+        let (baseline, _) = measure(1, false);
+        for (label, threads, prune_assets) in [
+            ("1 поток, без отсечения", 1, false),
+            ("1 поток, с отсечением", 1, true),
+            ("все потоки, с отсечением", 0, true),
+        ] {
+            let (best, games) = measure(threads, prune_assets);
+            println!(
+                "{:<26} {:>10.1?}  x{:<5.1} игр: {}",
+                label,
+                best,
+                baseline.as_secs_f64() / best.as_secs_f64().max(f64::EPSILON),
+                games
+            );
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
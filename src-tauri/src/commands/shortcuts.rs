@@ -0,0 +1,172 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::database::{self, GameEntry};
+
+// Все ярлыки лаунчера начинаются с этого префикса, так их легко отличить от чужих
+const SHORTCUT_PREFIX: &str = "rvnlauncher-";
+
+// Папка, из которой меню приложений берет пользовательские .desktop файлы
+fn applications_dir() -> Result<PathBuf, String> {
+    env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .map(|dir| dir.join("applications"))
+        .ok_or_else(|| "Не удалось определить папку для ярлыков".to_string())
+}
+
+fn shortcut_path(applications_dir: &Path, game_path: &str) -> PathBuf {
+    let safe_game_path = game_path.replace(|c: char| !c.is_alphanumeric(), "_");
+    applications_dir.join(format!("{}{}.desktop", SHORTCUT_PREFIX, safe_game_path))
+}
+
+// Игры, для которых нужно создать или удалить ярлыки: одна игра или все игры папки
+fn target_games(
+    app_data_dir: &Path,
+    game_path: Option<String>,
+    folder_id: Option<i64>,
+) -> Result<Vec<GameEntry>, String> {
+    match (game_path, folder_id) {
+        (Some(path), _) => database::find_game(&path)?
+            .map(|game| vec![game])
+            .ok_or_else(|| format!("Игра не найдена в библиотеке: {}", path)),
+        // Папка содержит все версии игры, ярлык нужен только для текущей
+        (None, Some(folder_id)) => database::load_titles(app_data_dir, Some(folder_id)),
+        (None, None) => Err("Укажите игру или папку".to_string()),
+    }
+}
+
+// Экранирование строкового значения по спецификации Desktop Entry
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::new();
+    for (i, c) in value.chars().enumerate() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            ' ' if i == 0 => escaped.push_str("\\s"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Аргумент строки Exec: всегда в кавычках, внутри экранируются ", `, $ и \,
+// а % удваивается, чтобы его не приняли за код поля (%f, %u...)
+fn quote_exec_arg(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        match c {
+            '"' | '`' | '$' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '%' => quoted.push_str("%%"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Чем запускать лаунчер из ярлыка. В AppImage current_exe указывает во временную
+// точку монтирования, которая исчезнет после выхода, поэтому берем путь к самому образу.
+fn launcher_executable() -> Result<PathBuf, String> {
+    if let Some(appimage) = env::var_os("APPIMAGE") {
+        return Ok(PathBuf::from(appimage));
+    }
+    env::current_exe().map_err(|e| e.to_string())
+}
+
+// Иконка из папки icons. В базе путь хранится как "icons\\file.png",
+// поэтому берем только имя файла, а не склеиваем путь как есть.
+fn icon_file(app_data_dir: &Path, game: &GameEntry) -> Option<PathBuf> {
+    let file_name = game.icon_path.as_deref()?.rsplit(['\\', '/']).next()?;
+    let icon = app_data_dir.join("icons").join(file_name);
+    icon.is_file().then_some(icon)
+}
+
+fn desktop_entry(launcher: &Path, app_data_dir: &Path, game: &GameEntry) -> String {
+    // Запуск идет через CLI лаунчера, поэтому время игры учитывается как обычно
+    let exec = [
+        launcher.to_string_lossy().as_ref(),
+        "launch",
+        game.path.as_str(),
+    ]
+    .iter()
+    .map(|arg| quote_exec_arg(arg))
+    .collect::<Vec<_>>()
+    .join(" ");
+    let working_dir = Path::new(&game.path).parent().unwrap_or(Path::new("/"));
+
+    let mut lines = vec![
+        "[Desktop Entry]".to_string(),
+        "Type=Application".to_string(),
+        format!("Name={}", escape_value(&game.name)),
+        format!("Exec={}", escape_value(&exec)),
+        format!("Path={}", escape_value(&working_dir.to_string_lossy())),
+        "Terminal=false".to_string(),
+        "Categories=Game;".to_string(),
+    ];
+    if let Some(description) = game.description.as_deref().and_then(|d| d.lines().next()) {
+        lines.push(format!("Comment={}", escape_value(description)));
+    }
+    if let Some(icon) = icon_file(app_data_dir, game) {
+        lines.push(format!("Icon={}", escape_value(&icon.to_string_lossy())));
+    }
+    lines.join("\n") + "\n"
+}
+
+// Создает .desktop файлы для игры или для всех игр папки и возвращает пути к ним
+#[tauri::command]
+pub fn create_desktop_shortcuts(
+    app: AppHandle,
+    game_path: Option<String>,
+    folder_id: Option<i64>,
+) -> Result<Vec<String>, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let games = target_games(&app_data_dir, game_path, folder_id)?;
+    let applications_dir = applications_dir()?;
+    fs::create_dir_all(&applications_dir).map_err(|e| e.to_string())?;
+    let launcher = launcher_executable()?;
+
+    let mut created = Vec::new();
+    for game in &games {
+        let path = shortcut_path(&applications_dir, &game.path);
+        fs::write(&path, desktop_entry(&launcher, &app_data_dir, game)).map_err(|e| e.to_string())?;
+        // Файловые менеджеры и рабочие столы запускают только исполняемые ярлыки
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).map_err(|e| e.to_string())?;
+        }
+        created.push(path.to_string_lossy().into_owned());
+    }
+    Ok(created)
+}
+
+// Удаляет ярлыки игры или всех игр папки. Возвращает количество удаленных файлов.
+#[tauri::command]
+pub fn remove_desktop_shortcuts(
+    app: AppHandle,
+    game_path: Option<String>,
+    folder_id: Option<i64>,
+) -> Result<usize, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let games = target_games(&app_data_dir, game_path, folder_id)?;
+    let applications_dir = applications_dir()?;
+
+    let mut removed = 0;
+    for game in &games {
+        let path = shortcut_path(&applications_dir, &game.path);
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| e.to_string())?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
@@ -0,0 +1,261 @@
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+use walkdir::WalkDir;
+
+use crate::commands::icons;
+use crate::commands::scanner::{self, GameInfo};
+use crate::database::{self, LibraryRoot};
+use crate::scan_rules::ScanRules;
+
+// Опрашиваем папки, а не подписываемся на события ФС: на сетевых дисках (SMB, NFS)
+// события изменений часто не приходят
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+// Новая папка считается распакованной, если ее содержимое не менялось столько времени
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+// Игры, найденные наблюдателем и ждущие подтверждения (режим "confirm").
+// Хранятся в памяти: после перезапуска их найдет обычное пересканирование библиотеки.
+static PENDING_GAMES: Lazy<Mutex<Vec<GameInfo>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+// Снимок содержимого папки: число файлов, общий размер и время последнего изменения
+#[derive(PartialEq, Eq, Clone, Copy)]
+struct Fingerprint {
+    files: u64,
+    bytes: u64,
+    latest: Option<SystemTime>,
+}
+
+fn fingerprint(dir: &Path) -> Fingerprint {
+    let mut fingerprint = Fingerprint {
+        files: 0,
+        bytes: 0,
+        latest: None,
+    };
+    for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
+        let Ok(metadata) = entry.metadata() else { continue };
+        if metadata.is_file() {
+            fingerprint.files += 1;
+            fingerprint.bytes += metadata.len();
+        }
+        if let Ok(modified) = metadata.modified() {
+            fingerprint.latest = fingerprint.latest.max(Some(modified));
+        }
+    }
+    fingerprint
+}
+
+// Новая папка, которая, возможно, еще распаковывается
+struct Candidate {
+    fingerprint: Fingerprint,
+    unchanged_since: Instant,
+}
+
+#[derive(Default)]
+struct WatcherState {
+    // Уже обработанные папки внутри каждой папки библиотеки (ключ - путь и глубокий поиск:
+    // при смене режима все папки ниже прежней глубины не должны считаться новыми)
+    known_dirs: HashMap<(String, bool), HashSet<PathBuf>>,
+    candidates: HashMap<PathBuf, Candidate>,
+}
+
+// Все папки, в которых сканер стал бы искать игры: с той же глубиной и черным списком,
+// поэтому игру во вложенной папке ("<root>/Новеллы/Game-1.0-pc") тоже видно
+fn watched_dirs(root: &Path, deep_search: bool, rules: &ScanRules) -> HashSet<PathBuf> {
+    let mut dirs = HashSet::new();
+    let walker = WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(scanner::max_depth(deep_search) - 1)
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            // Скрытые папки, в том числе временные папки install_from_archive
+            entry.file_type().is_dir() && !name.starts_with('.') && !rules.excludes(&name, true)
+        });
+    for entry in walker.filter_map(|e| e.ok()) {
+        dirs.insert(entry.into_path());
+    }
+    dirs
+}
+
+fn watcher_mode() -> String {
+    database::db_get_setting("libraryWatcher".to_string())
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| "off".to_string())
+}
+
+// Запускается вместе с окном. Режим берется из настройки libraryWatcher на каждом опросе:
+// "off" - не следить, "auto" - сразу добавлять игры, "confirm" - складывать в очередь.
+pub fn start(app: AppHandle, app_data_dir: PathBuf) {
+    std::thread::spawn(move || {
+        let mut state = WatcherState::default();
+        loop {
+            let mode = watcher_mode();
+            if mode == "off" {
+                // При включении заново запоминаем текущее содержимое, а не считаем все новым
+                state = WatcherState::default();
+            } else {
+                match database::db_get_library_roots() {
+                    Ok(roots) => {
+                        for root in &roots {
+                            poll_root(&app, &app_data_dir, &mut state, root, &mode);
+                        }
+                    }
+                    Err(e) => eprintln!("Наблюдатель библиотеки: {}", e),
+                }
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+fn poll_root(
+    app: &AppHandle,
+    app_data_dir: &Path,
+    state: &mut WatcherState,
+    root: &LibraryRoot,
+    mode: &str,
+) {
+    let root_path = Path::new(&root.path);
+    if !root_path.is_dir() {
+        return;
+    }
+    let rules = ScanRules::load().with_globs(&root.dir_blacklist, &root.file_blacklist);
+    let current = watched_dirs(root_path, root.deep_search, &rules);
+    let Some(known) = state.known_dirs.get_mut(&(root.path.clone(), root.deep_search)) else {
+        // Первый опрос папки: все, что в ней уже есть, новым не считаем
+        state.known_dirs.insert((root.path.clone(), root.deep_search), current);
+        return;
+    };
+    known.retain(|dir| current.contains(dir));
+
+    let new_dirs: HashSet<&PathBuf> = current.difference(known).collect();
+    // Подпапки новой папки ждут вместе с ней: сканируется только верхняя
+    let top_dirs: Vec<PathBuf> = new_dirs
+        .iter()
+        .filter(|dir| !dir.ancestors().skip(1).any(|parent| new_dirs.contains(&parent.to_path_buf())))
+        .map(|dir| dir.to_path_buf())
+        .collect();
+    for dir in &top_dirs {
+        let fingerprint = fingerprint(dir);
+        let candidate = state.candidates.entry(dir.clone()).or_insert(Candidate {
+            fingerprint,
+            unchanged_since: Instant::now(),
+        });
+        if candidate.fingerprint != fingerprint {
+            candidate.fingerprint = fingerprint;
+            candidate.unchanged_since = Instant::now();
+            continue;
+        }
+        // Пустая папка - распаковка, скорее всего, еще не началась
+        if fingerprint.files == 0 || candidate.unchanged_since.elapsed() < STABLE_AFTER {
+            continue;
+        }
+
+        state.candidates.remove(dir);
+        known.extend(current.iter().filter(|path| path.starts_with(dir)).cloned());
+        handle_new_dir(app, app_data_dir, root, &rules, dir, mode);
+    }
+    state.candidates.retain(|dir, _| top_dirs.contains(dir));
+}
+
+// Распаковка закончилась: сканируем только новую папку
+fn handle_new_dir(
+    app: &AppHandle,
+    app_data_dir: &Path,
+    root: &LibraryRoot,
+    rules: &ScanRules,
+    dir: &Path,
+    mode: &str,
+) {
+    let found = scanner::scan_directory(&dir.to_string_lossy(), root.deep_search, rules);
+    for info in found.games {
+        if database::find_game(info.path()).ok().flatten().is_some() {
+            continue;
+        }
+        if mode == "auto" {
+            let entry = info.to_game_entry();
+            if let Err(e) = database::db_add_game(entry.clone()) {
+                eprintln!("Наблюдатель не смог добавить {}: {}", entry.path, e);
+                continue;
+            }
+            if let Err(e) = icons::extract_icon_for_game(app_data_dir, &entry) {
+                eprintln!("Иконка для {} не извлечена: {}", entry.path, e);
+            }
+            let _ = app.emit("library-game-added", entry);
+        } else {
+            let mut pending = PENDING_GAMES.lock().unwrap();
+            if pending.iter().all(|game| game.path() != info.path()) {
+                pending.push(info.clone());
+                let _ = app.emit("library-game-detected", info);
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_pending_games() -> Vec<GameInfo> {
+    PENDING_GAMES.lock().unwrap().clone()
+}
+
+// Добавляет игру из очереди в библиотеку (accept = true) или просто убирает ее из очереди
+#[tauri::command]
+pub fn resolve_pending_game(
+    app: AppHandle,
+    game_path: String,
+    accept: bool,
+) -> Result<(), String> {
+    let info = {
+        let mut pending = PENDING_GAMES.lock().unwrap();
+        let index = pending
+            .iter()
+            .position(|game| game.path() == game_path)
+            .ok_or_else(|| format!("Игры нет в очереди: {}", game_path))?;
+        pending.remove(index)
+    };
+    if !accept {
+        return Ok(());
+    }
+    let entry = info.to_game_entry();
+    database::db_add_game(entry.clone())?;
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    if let Err(e) = icons::extract_icon_for_game(&app_data_dir, &entry) {
+        eprintln!("Иконка для {} не извлечена: {}", entry.path, e);
+    }
+    // То же событие, что и в режиме auto: по нему библиотека перечитывает список
+    let _ = app.emit("library-game-added", entry);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watched_dirs_follow_scanner_depth_and_blacklist() {
+        let root = std::env::temp_dir().join(format!("watcher-test-{}", std::process::id()));
+        for dir in ["Новеллы/Game-1.0-pc/extras", "Game/.partial", "Game/renpy", ".Game.partial"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let rules = ScanRules::builtin();
+        let relative = |deep_search| -> HashSet<PathBuf> {
+            watched_dirs(&root, deep_search, &rules)
+                .into_iter()
+                .map(|dir| dir.strip_prefix(&root).unwrap().to_path_buf())
+                .collect()
+        };
+
+        let shallow = relative(false);
+        assert!(shallow.contains(Path::new("Новеллы/Game-1.0-pc")));
+        assert!(!shallow.contains(Path::new("Новеллы/Game-1.0-pc/extras")));
+        assert!(!shallow.contains(Path::new("Game/.partial")));
+        assert!(!shallow.contains(Path::new(".Game.partial")));
+        assert!(!shallow.contains(Path::new("Game/renpy")));
+        assert!(relative(true).contains(Path::new("Новеллы/Game-1.0-pc/extras")));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
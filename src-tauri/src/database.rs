@@ -1,10 +1,19 @@
 use once_cell::sync::Lazy;
-use rusqlite::{Connection, Result};
+use rusqlite::{Connection, OptionalExtension, Result};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
 use std::sync::Mutex;
 // <--- ВАЖНОЕ ИЗМЕНЕНИЕ ЗДЕСЬ: импортируем AppHandle и Manager
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
 
+use crate::engines;
+use crate::process_tree;
+use crate::renpy;
+use crate::scan_rules;
+use crate::titles;
+
 // Глобальная, потокобезопасная ссылка на подключение к БД
 static DB: Lazy<Mutex<Option<Connection>>> = Lazy::new(|| Mutex::new(None));
 
@@ -22,6 +31,21 @@ pub struct GameEntry {
     pub rating: i32,
     pub is_hidden: bool,
     pub completion_percent: i32,
+    // Движок, определенный по структуре папки: renpy, rpgmaker, unity, godot, kirikiri, html
+    pub engine: Option<String>,
+    // Откуда взяты имя и версия: "file" / "path" (угадано сканером), "options.rpy" или "user"
+    pub name_source: Option<String>,
+    pub version_source: Option<String>,
+    // Метаданные Ren'Py из options.rpy и версия SDK из папки renpy/
+    pub save_directory: Option<String>,
+    pub build_name: Option<String>,
+    pub engine_version: Option<String>,
+    // Установленные версии одной игры объединены общим ключом названия (см. titles.rs).
+    // В библиотеке название представлено текущей версией.
+    #[serde(default)]
+    pub title_key: Option<String>,
+    #[serde(default)]
+    pub is_current_version: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -30,10 +54,127 @@ pub struct Folder {
     pub name: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PlaySession {
+    pub id: i64,
+    pub game_path: String,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub duration_seconds: i64,
+    pub exit_code: Option<i32>,
+    pub game_version: Option<String>,
+    pub last_checkpoint: Option<String>,
+    pub interrupted: bool,
+    // Отчет о падении игры, если он появился за время сессии
+    pub crash: Option<SessionCrash>,
+}
+
+// Падение, найденное по отчетам Ren'Py (traceback.txt, errors.txt)
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SessionCrash {
+    pub report_path: String,
+    pub exception: Option<String>,
+    pub script_file: Option<String>,
+    pub script_line: Option<i64>,
+}
+
+// Профиль запуска игры. Аргументы и переменные окружения хранятся в базе как JSON.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LaunchProfile {
+    pub id: Option<i64>,
+    pub game_path: String,
+    pub name: String,
+    pub arguments: Vec<String>,
+    pub env_vars: BTreeMap<String, String>,
+    pub working_dir: Option<String>,
+    // Команда-обертка, например "gamemoderun" или "taskset -c 0-3"
+    pub wrapper: Option<String>,
+    pub is_default: bool,
+}
+
+// Установленный Wine или Proton. kind: "wine" или "proton", path - исполняемый файл
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Runner {
+    pub id: Option<i64>,
+    pub name: String,
+    pub kind: String,
+    pub path: String,
+    pub is_default: bool,
+}
+
+// Папка с играми, которую лаунчер пересканирует сам. Черные списки хранятся как JSON:
+// это glob-шаблоны только для этой папки, в дополнение к общим правилам из scan_rules.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LibraryRoot {
+    pub id: Option<i64>,
+    pub path: String,
+    pub deep_search: bool,
+    pub dir_blacklist: Vec<String>,
+    pub file_blacklist: Vec<String>,
+    pub last_scan_at: Option<String>,
+}
+
+// Правило исключения для сканера. target: "dir" или "file", kind: "exact", "glob" или "regex".
+// Встроенные правила (is_builtin) можно менять и отключать, db_reset_scan_rules возвращает их.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScanRule {
+    pub id: Option<i64>,
+    pub target: String,
+    pub kind: String,
+    pub pattern: String,
+    pub enabled: bool,
+    pub is_builtin: bool,
+}
+
+// Команда, которая выполняется перед запуском игры или после выхода из нее.
+// game_path = None - глобальный хук для всех игр. stage: "pre_launch" или "post_exit".
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GameHook {
+    pub id: Option<i64>,
+    pub game_path: Option<String>,
+    pub stage: String,
+    pub command: String,
+    pub timeout_seconds: i64,
+    // Для pre_launch: отменить запуск игры, если хук завершился с ошибкой
+    pub abort_on_failure: bool,
+    pub enabled: bool,
+    pub sort_order: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HookRun {
+    pub id: i64,
+    pub hook_id: i64,
+    pub game_path: String,
+    pub session_id: Option<i64>,
+    pub stage: String,
+    pub started_at: String,
+    pub duration_ms: i64,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub output: String,
+}
+
+// Формат дат, в котором хранятся last_played и время сессий
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// Сессии без tracker_pid (записанные до v15) считаются оборванными, если их контрольная точка
+// старше этого значения. Свежие незакрытые сессии могут принадлежать другому живому процессу лаунчера.
+const STALE_SESSION_MINUTES: i64 = 2;
+
+const PLAY_SESSION_COLUMNS: &str =
+    "id, game_path, started_at, ended_at, duration_seconds, exit_code, game_version, last_checkpoint, interrupted, crash_report, crash_exception, crash_script_file, crash_script_line";
+
 pub fn init(app_handle: &AppHandle) {
     let app_dir = app_handle.path().app_data_dir().expect("Failed to get app data dir");
+    init_at(&app_dir);
+}
+
+// Открывает базу в указанной папке данных. Используется и окном, и CLI,
+// у которого нет AppHandle.
+pub fn init_at(app_dir: &Path) {
     if !app_dir.exists() {
-        std::fs::create_dir_all(&app_dir).expect("Failed to create app data dir");
+        std::fs::create_dir_all(app_dir).expect("Failed to create app data dir");
     }
     let db_path = app_dir.join("launcher.db");
 
@@ -44,11 +185,31 @@ pub fn init(app_handle: &AppHandle) {
     // Это позволяет безопасно добавлять новые миграции в будущем.
     migrate_v1_initial_tables(&conn).expect("V1 migration failed");
     migrate_v2_add_completion_percent(&conn).expect("V2 migration failed");
+    migrate_v3_play_sessions(&conn).expect("V3 migration failed");
+    migrate_v4_session_checkpoints(&conn).expect("V4 migration failed");
+    migrate_v5_launch_profiles(&conn).expect("V5 migration failed");
+    migrate_v6_runners(&conn).expect("V6 migration failed");
+    migrate_v7_hooks(&conn).expect("V7 migration failed");
+    migrate_v8_auto_backup(&conn).expect("V8 migration failed");
+    migrate_v9_session_crashes(&conn).expect("V9 migration failed");
+    migrate_v10_game_engine(&conn).expect("V10 migration failed");
+    migrate_v11_renpy_metadata(&conn).expect("V11 migration failed");
+    migrate_v12_library_roots(&conn).expect("V12 migration failed");
+    migrate_v13_scan_rules(&conn).expect("V13 migration failed");
+    migrate_v14_game_titles(&conn).expect("V14 migration failed");
+    migrate_v15_session_tracker(&conn).expect("V15 migration failed");
+    migrate_v16_icon_failures(&conn).expect("V16 migration failed");
+    migrate_v17_python_scan_rule(&conn).expect("V17 migration failed");
+    migrate_v18_titles(&conn).expect("V18 migration failed");
     // Когда понадобится новая миграция, просто добавишь сюда вызов:
-    // migrate_v3_add_tags_table(&conn).expect("V3 migration failed");
+    // migrate_vN_add_tags_table(&conn).expect("VN migration failed");
+
+    // Сессии, которые не успели закончиться (лаунчер убили, пропало питание)
+    recover_interrupted_sessions(&conn).expect("Failed to recover interrupted sessions");
 
     *DB.lock().unwrap() = Some(conn);
-    println!("Database initialized and migrations applied at: {:?}", db_path);
+    // В stderr, чтобы не смешивать с выводом команд CLI
+    eprintln!("Database initialized and migrations applied at: {:?}", db_path);
 }
 
 // --- ФУНКЦИИ МИГРАЦИИ ---
@@ -100,18 +261,445 @@ fn migrate_v2_add_completion_percent(conn: &Connection) -> Result<()> {
     let has_column = columns.filter_map(Result::ok).any(|col_name| col_name == "completion_percent");
 
     if !has_column {
-        println!("Applying V2 migration: Adding 'completion_percent' column...");
+        eprintln!("Applying V2 migration: Adding 'completion_percent' column...");
         conn.execute(
             "ALTER TABLE games ADD COLUMN completion_percent INTEGER DEFAULT 0",
             [],
         )?;
-        println!("V2 migration applied successfully.");
+        eprintln!("V2 migration applied successfully.");
+    }
+    Ok(())
+}
+
+// Миграция v3: история игровых сессий.
+// Уже накопленное время переносится одной "импортированной" сессией,
+// чтобы общее время всегда можно было пересчитать как сумму сессий.
+fn migrate_v3_play_sessions(conn: &Connection) -> Result<()> {
+    let table_exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'play_sessions')",
+        [],
+        |row| row.get(0),
+    )?;
+
+    if !table_exists {
+        eprintln!("Applying V3 migration: Creating 'play_sessions' table...");
+        conn.execute_batch(
+            "BEGIN;
+            CREATE TABLE play_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                game_path TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                ended_at TEXT,
+                duration_seconds INTEGER NOT NULL DEFAULT 0,
+                exit_code INTEGER,
+                game_version TEXT,
+                FOREIGN KEY(game_path) REFERENCES games(path) ON DELETE CASCADE
+            );
+            CREATE INDEX idx_play_sessions_game_path ON play_sessions(game_path);
+            INSERT INTO play_sessions (game_path, started_at, ended_at, duration_seconds, game_version)
+                SELECT path,
+                       datetime(COALESCE(last_played, datetime('now', 'localtime')), '-' || play_time_seconds || ' seconds'),
+                       COALESCE(last_played, datetime('now', 'localtime')),
+                       play_time_seconds,
+                       version
+                FROM games WHERE play_time_seconds > 0;
+            COMMIT;",
+        )?;
+        eprintln!("V3 migration applied successfully.");
+    }
+    Ok(())
+}
+
+// Миграция v4: контрольные точки активных сессий для восстановления после сбоя
+fn migrate_v4_session_checkpoints(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(play_sessions)")?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+
+    let has_column = columns.filter_map(Result::ok).any(|col_name| col_name == "last_checkpoint");
+
+    if !has_column {
+        eprintln!("Applying V4 migration: Adding session checkpoint columns...");
+        conn.execute_batch(
+            "BEGIN;
+            ALTER TABLE play_sessions ADD COLUMN last_checkpoint TEXT;
+            ALTER TABLE play_sessions ADD COLUMN interrupted BOOLEAN NOT NULL DEFAULT FALSE;
+            INSERT OR IGNORE INTO settings (key, value) VALUES ('interruptedSessions', 'close');
+            COMMIT;",
+        )?;
+        eprintln!("V4 migration applied successfully.");
+    }
+    Ok(())
+}
+
+// Миграция v5: профили запуска (аргументы, переменные окружения, обертка)
+fn migrate_v5_launch_profiles(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS launch_profiles (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_path TEXT NOT NULL,
+            name TEXT NOT NULL,
+            arguments TEXT NOT NULL DEFAULT '[]',
+            env_vars TEXT NOT NULL DEFAULT '{}',
+            working_dir TEXT,
+            wrapper TEXT,
+            is_default BOOLEAN NOT NULL DEFAULT FALSE,
+            FOREIGN KEY(game_path) REFERENCES games(path) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_launch_profiles_game_path ON launch_profiles(game_path);",
+    )?;
+    Ok(())
+}
+
+// Миграция v6: раннеры Wine/Proton для Windows-игр на Linux
+fn migrate_v6_runners(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS runners (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            path TEXT NOT NULL UNIQUE,
+            is_default BOOLEAN NOT NULL DEFAULT FALSE
+        );
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('winePrefixMode', 'shared');",
+    )?;
+
+    let mut stmt = conn.prepare("PRAGMA table_info(games)")?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+
+    let has_column = columns.filter_map(Result::ok).any(|col_name| col_name == "runner_id");
+
+    if !has_column {
+        eprintln!("Applying V6 migration: Adding runner columns to 'games'...");
+        conn.execute_batch(
+            "BEGIN;
+            ALTER TABLE games ADD COLUMN runner_id INTEGER;
+            ALTER TABLE games ADD COLUMN wine_prefix_mode TEXT;
+            COMMIT;",
+        )?;
+        eprintln!("V6 migration applied successfully.");
+    }
+    Ok(())
+}
+
+// Миграция v7: хуки перед запуском и после выхода из игры и журнал их запусков
+fn migrate_v7_hooks(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS game_hooks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_path TEXT,
+            stage TEXT NOT NULL,
+            command TEXT NOT NULL,
+            timeout_seconds INTEGER NOT NULL DEFAULT 30,
+            abort_on_failure BOOLEAN NOT NULL DEFAULT TRUE,
+            enabled BOOLEAN NOT NULL DEFAULT TRUE,
+            sort_order INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE TABLE IF NOT EXISTS hook_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            hook_id INTEGER NOT NULL,
+            game_path TEXT NOT NULL,
+            session_id INTEGER,
+            stage TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            duration_ms INTEGER NOT NULL,
+            exit_code INTEGER,
+            timed_out BOOLEAN NOT NULL DEFAULT FALSE,
+            output TEXT NOT NULL DEFAULT ''
+        );
+        CREATE INDEX IF NOT EXISTS idx_hook_runs_game_path ON hook_runs(game_path);",
+    )?;
+    Ok(())
+}
+
+// Миграция v8: автоматический бэкап сохранений после выхода из игры.
+// auto_backup = NULL означает "как в глобальной настройке autoBackupOnExit".
+fn migrate_v8_auto_backup(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(games)")?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+
+    let has_column = columns.filter_map(Result::ok).any(|col_name| col_name == "auto_backup");
+
+    if !has_column {
+        eprintln!("Applying V8 migration: Adding auto backup columns...");
+        conn.execute_batch(
+            "BEGIN;
+            ALTER TABLE games ADD COLUMN auto_backup BOOLEAN;
+            ALTER TABLE games ADD COLUMN backup_fingerprint TEXT;
+            INSERT OR IGNORE INTO settings (key, value) VALUES ('autoBackupOnExit', 'false');
+            COMMIT;",
+        )?;
+        eprintln!("V8 migration applied successfully.");
+    }
+    Ok(())
+}
+
+fn migrate_v9_session_crashes(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(play_sessions)")?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+
+    let has_column = columns.filter_map(Result::ok).any(|col_name| col_name == "crash_report");
+
+    if !has_column {
+        eprintln!("Applying V9 migration: Adding crash columns to 'play_sessions'...");
+        conn.execute_batch(
+            "BEGIN;
+            ALTER TABLE play_sessions ADD COLUMN crash_report TEXT;
+            ALTER TABLE play_sessions ADD COLUMN crash_exception TEXT;
+            ALTER TABLE play_sessions ADD COLUMN crash_script_file TEXT;
+            ALTER TABLE play_sessions ADD COLUMN crash_script_line INTEGER;
+            COMMIT;",
+        )?;
+        eprintln!("V9 migration applied successfully.");
+    }
+    Ok(())
+}
+
+fn migrate_v10_game_engine(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(games)")?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+
+    let has_column = columns.filter_map(Result::ok).any(|col_name| col_name == "engine");
+
+    if !has_column {
+        eprintln!("Applying V10 migration: Adding 'engine' column and detecting engines...");
+        conn.execute("ALTER TABLE games ADD COLUMN engine TEXT", [])?;
+
+        // Уже добавленным играм движок определяем по их папкам.
+        // Недоступные сейчас папки (отключенный диск) дозаполнятся при следующем сканировании.
+        let paths = conn
+            .prepare("SELECT path FROM games")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<String>>>()?;
+        for path in paths {
+            if let Some(engine) = engines::detect_engine_for_game(&path) {
+                conn.execute("UPDATE games SET engine = ?2 WHERE path = ?1", (&path, engine))?;
+            }
+        }
+        eprintln!("V10 migration applied successfully.");
+    }
+    Ok(())
+}
+
+fn migrate_v11_renpy_metadata(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(games)")?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+
+    let has_column = columns.filter_map(Result::ok).any(|col_name| col_name == "name_source");
+
+    if !has_column {
+        eprintln!("Applying V11 migration: Adding game metadata columns...");
+        conn.execute_batch(
+            "BEGIN;
+            ALTER TABLE games ADD COLUMN name_source TEXT;
+            ALTER TABLE games ADD COLUMN version_source TEXT;
+            ALTER TABLE games ADD COLUMN save_directory TEXT;
+            ALTER TABLE games ADD COLUMN build_name TEXT;
+            ALTER TABLE games ADD COLUMN engine_version TEXT;
+            COMMIT;",
+        )?;
+
+        // Имена и версии уже добавленных игр могли быть исправлены вручную, поэтому
+        // для них заполняем только служебные поля
+        let paths = conn
+            .prepare("SELECT path FROM games WHERE engine = 'renpy'")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<String>>>()?;
+        for path in paths {
+            let Some(game_dir) = Path::new(&path).parent() else { continue };
+            let metadata = renpy::read_metadata(game_dir);
+            conn.execute(
+                "UPDATE games SET save_directory = ?2, build_name = ?3, engine_version = ?4 WHERE path = ?1",
+                (&path, &metadata.save_directory, &metadata.build_name, &metadata.sdk_version),
+            )?;
+        }
+        eprintln!("V11 migration applied successfully.");
+    }
+    Ok(())
+}
+
+fn migrate_v12_library_roots(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS library_roots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            path TEXT NOT NULL UNIQUE,
+            deep_search BOOLEAN NOT NULL DEFAULT FALSE,
+            dir_blacklist TEXT NOT NULL DEFAULT '[]',
+            file_blacklist TEXT NOT NULL DEFAULT '[]',
+            last_scan_at TEXT
+        );",
+    )?;
+    Ok(())
+}
+
+fn insert_builtin_scan_rules(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "INSERT INTO scan_rules (target, kind, pattern, enabled, is_builtin) VALUES (?1, ?2, ?3, TRUE, TRUE)",
+    )?;
+    for (target, kind, pattern) in scan_rules::BUILTIN_RULES {
+        stmt.execute((target, kind, pattern))?;
+    }
+    Ok(())
+}
+
+fn migrate_v13_scan_rules(conn: &Connection) -> Result<()> {
+    let table_exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'scan_rules')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !table_exists {
+        eprintln!("Applying V13 migration: creating scan_rules table...");
+        conn.execute_batch(
+            "CREATE TABLE scan_rules (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                target TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                pattern TEXT NOT NULL,
+                enabled BOOLEAN NOT NULL DEFAULT TRUE,
+                is_builtin BOOLEAN NOT NULL DEFAULT FALSE
+            );",
+        )?;
+        insert_builtin_scan_rules(conn)?;
+        eprintln!("V13 migration applied successfully.");
+    }
+    Ok(())
+}
+
+fn migrate_v14_game_titles(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(games)")?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+
+    let has_column = columns.filter_map(Result::ok).any(|col_name| col_name == "title_key");
+
+    if !has_column {
+        eprintln!("Applying V14 migration: grouping installed versions into titles...");
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(
+            "ALTER TABLE games ADD COLUMN title_key TEXT;
+            ALTER TABLE games ADD COLUMN is_current_version BOOLEAN NOT NULL DEFAULT TRUE;",
+        )?;
+
+        let games = tx
+            .prepare("SELECT path, name, name_source, version FROM games")?
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        let mut titles_by_key: HashMap<String, Vec<(String, Option<String>)>> = HashMap::new();
+        for (path, name, name_source, version) in games {
+            let key = titles::title_key(&path, &name, name_source.as_deref());
+            tx.execute("UPDATE games SET title_key = ?2 WHERE path = ?1", (&path, &key))?;
+            titles_by_key.entry(key).or_default().push((path, version));
+        }
+
+        // Только отмечаем текущую (самую новую) версию: данные версий не трогаем,
+        // общие для названия оценку и заметки хранит таблица titles (миграция v18)
+        for versions in titles_by_key.values().filter(|versions| versions.len() > 1) {
+            if let Some((current_path, _)) = versions
+                .iter()
+                .max_by(|a, b| titles::compare_versions(a.1.as_deref(), b.1.as_deref()))
+            {
+                set_current_version(&tx, current_path)?;
+            }
+        }
+        tx.commit()?;
+        eprintln!("V14 migration applied successfully.");
+    }
+    Ok(())
+}
+
+// Миграция v15: PID процесса лаунчера, который ведет сессию. По нему при старте видно,
+// что сессия оборвана, не дожидаясь, пока устареет ее контрольная точка.
+fn migrate_v15_session_tracker(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(play_sessions)")?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+
+    let has_column = columns.filter_map(Result::ok).any(|col_name| col_name == "tracker_pid");
+
+    if !has_column {
+        eprintln!("Applying V15 migration: Adding tracker_pid column to 'play_sessions'...");
+        conn.execute("ALTER TABLE play_sessions ADD COLUMN tracker_pid INTEGER", [])?;
+        eprintln!("V15 migration applied successfully.");
+    }
+    Ok(())
+}
+
+// Миграция v16: .exe, из которых не удалось достать иконку. Без этого каждое
+// сканирование заново читало бы ресурсы всех таких файлов.
+fn migrate_v16_icon_failures(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS icon_failures (
+            source_path TEXT PRIMARY KEY,
+            modified_at INTEGER NOT NULL
+        );",
+    )?;
+    Ok(())
+}
+
+// Миграция v17: встроенное правило python*.exe пропускало игры с таким началом имени,
+// его заменяют точные python.exe и pythonw.exe. Измененное пользователем правило не трогаем.
+fn migrate_v17_python_scan_rule(conn: &Connection) -> Result<()> {
+    let updated = conn.execute(
+        "UPDATE scan_rules SET kind = 'exact', pattern = 'python.exe'
+         WHERE is_builtin AND target = 'file' AND kind = 'glob' AND pattern = 'python*.exe'",
+        [],
+    )?;
+    if updated > 0 {
+        eprintln!("Applying V17 migration: replacing the python*.exe scan rule...");
+        conn.execute(
+            "INSERT INTO scan_rules (target, kind, pattern, enabled, is_builtin)
+             SELECT 'file', 'exact', 'pythonw.exe', enabled, TRUE FROM scan_rules
+             WHERE is_builtin AND target = 'file' AND kind = 'exact' AND pattern = 'python.exe'",
+            [],
+        )?;
+        eprintln!("V17 migration applied successfully.");
+    }
+    Ok(())
+}
+
+// Миграция v18: данные уровня названия (имя, оценка, заметки, скрытие, прогресс) для игр
+// с несколькими версиями. Строки версий в games при объединении не меняются, поэтому
+// отделенная версия возвращается со своими прежними данными.
+fn migrate_v18_titles(conn: &Connection) -> Result<()> {
+    let table_exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'titles')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !table_exists {
+        eprintln!("Applying V18 migration: creating titles table...");
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(
+            "CREATE TABLE titles (
+                title_key TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                name_source TEXT,
+                rating INTEGER NOT NULL DEFAULT 0,
+                description TEXT,
+                is_hidden BOOLEAN NOT NULL DEFAULT FALSE,
+                completion_percent INTEGER NOT NULL DEFAULT 0
+            );",
+        )?;
+        let grouped_keys = tx
+            .prepare("SELECT title_key FROM games WHERE title_key IS NOT NULL GROUP BY title_key HAVING COUNT(*) > 1")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>>>()?;
+        for title_key in grouped_keys {
+            ensure_title_row(&tx, &title_key)?;
+        }
+        tx.commit()?;
+        eprintln!("V18 migration applied successfully.");
     }
     Ok(())
 }
 
 // Пример будущей миграции
-// fn migrate_v3_add_tags_table(conn: &Connection) -> Result<()> {
+// fn migrate_vN_add_tags_table(conn: &Connection) -> Result<()> {
 //     conn.execute_batch(
 //         "CREATE TABLE IF NOT EXISTS tags ( ... );
 //          CREATE TABLE IF NOT EXISTS game_tags ( ... );"
@@ -133,146 +721,665 @@ where
     }
 }
 
+// Колонки games в том порядке, в котором их читает map_game.
+// Явный список вместо `SELECT *`: миграции добавляют колонки в конец таблицы.
+// Данные уровня названия берутся из titles, если версия входит в название с такой строкой.
+const GAME_COLUMNS: &str =
+    "g.path, COALESCE(t.name, g.name), g.game_type, g.play_time_seconds, g.icon_path, COALESCE(t.description, g.description), g.version, g.last_played, COALESCE(t.rating, g.rating), COALESCE(t.is_hidden, g.is_hidden), COALESCE(t.completion_percent, g.completion_percent), g.engine, COALESCE(t.name_source, g.name_source), g.version_source, g.save_directory, g.build_name, g.engine_version, g.title_key, g.is_current_version";
+// Источник для GAME_COLUMNS
+const GAME_TABLES: &str = "games g LEFT JOIN titles t ON t.title_key = g.title_key";
+
+fn map_game(row: &rusqlite::Row) -> Result<GameEntry> {
+    Ok(GameEntry {
+        path: row.get(0)?,
+        name: row.get(1)?,
+        game_type: row.get(2)?,
+        play_time_seconds: row.get(3)?,
+        icon_path: row.get(4)?,
+        icon_url: None,
+        description: row.get(5)?,
+        version: row.get(6)?,
+        last_played: row.get(7)?,
+        rating: row.get(8)?,
+        is_hidden: row.get(9)?,
+        completion_percent: row.get(10)?,
+        engine: row.get(11)?,
+        name_source: row.get(12)?,
+        version_source: row.get(13)?,
+        save_directory: row.get(14)?,
+        build_name: row.get(15)?,
+        engine_version: row.get(16)?,
+        title_key: row.get(17)?,
+        is_current_version: row.get(18)?,
+    })
+}
+
+// Находит игру в базе по пути. Используется бэкендом (например, лаунчером),
+// чтобы работать только с играми, которые действительно есть в библиотеке.
+pub fn find_game(path: &str) -> Result<Option<GameEntry>, String> {
+    with_db(|conn| {
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM {} WHERE g.path = ?1", GAME_COLUMNS, GAME_TABLES))?;
+        let mut rows = stmt.query_map([path], map_game)?;
+        rows.next().transpose()
+    })
+}
+
+// Библиотека по названиям: по одной текущей версии на название,
+// с общим временем игры и датой последнего запуска по всем версиям
 #[tauri::command]
 pub fn db_get_games(app: AppHandle, folder_id: Option<i64>) -> Result<Vec<GameEntry>, String> {
     let app_data_dir = app.path().app_data_dir().expect("Could not get app data dir");
-    
-    with_db(|conn| {
-        // --- Создаем замыкание (closure) для маппинга, чтобы не дублировать код ---
-        // `move` здесь нужно, чтобы замыкание стало "владельцем" `app_data_dir`
-        let map_row = move |row: &rusqlite::Row| {
-            let icon_path_opt: Option<String> = row.get(4)?;
-            
-            // Твоя логика для icon_url
-            // Мы клонируем app_data_dir, чтобы передать его в замыкание
-            let icon_url = icon_path_opt.map(|rel_path| {
-                app_data_dir.join(rel_path).to_string_lossy().into_owned()
-            });
-
-            Ok(GameEntry {
-                path: row.get(0)?,
-                name: row.get(1)?,
-                game_type: row.get(2)?,
-                play_time_seconds: row.get(3)?,
-                icon_path: row.get(4)?,
-                description: row.get(5)?,
-                version: row.get(6)?,
-                last_played: row.get(7)?,
-                rating: row.get(8)?,
-                is_hidden: row.get(9)?,
-                completion_percent: row.get(10)?,
-                icon_url,
+    load_titles(&app_data_dir, folder_id)
+}
+
+pub fn load_titles(app_data_dir: &Path, folder_id: Option<i64>) -> Result<Vec<GameEntry>, String> {
+    let all_games = load_games(app_data_dir, None)?;
+    let mut totals: HashMap<String, (i64, Option<String>)> = HashMap::new();
+    for game in &all_games {
+        let key = game.title_key.clone().unwrap_or_else(|| game.path.clone());
+        let (play_time, last_played) = totals.entry(key).or_default();
+        *play_time += game.play_time_seconds;
+        if game.last_played > *last_played {
+            *last_played = game.last_played.clone();
+        }
+    }
+
+    // Название в папке, если в ней лежит хоть одна его версия
+    let folder_keys: Option<HashSet<String>> = match folder_id {
+        Some(_) => Some(
+            load_games(app_data_dir, folder_id)?
+                .into_iter()
+                .map(|game| game.title_key.unwrap_or(game.path))
+                .collect(),
+        ),
+        None => None,
+    };
+    Ok(all_games
+        .into_iter()
+        .filter(|game| game.is_current_version)
+        .filter(|game| {
+            folder_keys.as_ref().is_none_or(|keys| {
+                keys.contains(game.title_key.as_ref().unwrap_or(&game.path))
             })
-        };
+        })
+        .map(|mut game| {
+            let key = game.title_key.clone().unwrap_or_else(|| game.path.clone());
+            if let Some((play_time, last_played)) = totals.remove(&key) {
+                game.play_time_seconds = play_time;
+                game.last_played = last_played;
+            }
+            game
+        })
+        .collect())
+}
+
+pub fn load_games(app_data_dir: &Path, folder_id: Option<i64>) -> Result<Vec<GameEntry>, String> {
+    let mut games = with_db(|conn| {
         // --- В зависимости от folder_id, выполняем разный код ---
         if let Some(id) = folder_id {
             // --- Логика для конкретной папки ---
-            let sql = "SELECT g.* FROM games g JOIN game_folders gf ON g.path = gf.game_path WHERE gf.folder_id = ?1";
-            let mut stmt = conn.prepare(sql)?;
-            let game_iter = stmt.query_map([id], map_row)?;
+            let sql = format!(
+                "SELECT {} FROM {} JOIN game_folders gf ON g.path = gf.game_path WHERE gf.folder_id = ?1",
+                GAME_COLUMNS, GAME_TABLES
+            );
+            let mut stmt = conn.prepare(&sql)?;
+            let game_iter = stmt.query_map([id], map_game)?;
             
             // Собираем результаты в вектор
             game_iter.collect::<Result<Vec<GameEntry>, _>>()
         } else {
             // --- Логика для всех игр ---
-            let sql = "SELECT * FROM games";
-            let mut stmt = conn.prepare(sql)?;
-            let game_iter = stmt.query_map([], map_row)?;
+            let sql = format!("SELECT {} FROM {}", GAME_COLUMNS, GAME_TABLES);
+            let mut stmt = conn.prepare(&sql)?;
+            let game_iter = stmt.query_map([], map_game)?;
             
             // Собираем результаты в вектор
             game_iter.collect::<Result<Vec<GameEntry>, _>>()
         }
+    })?;
+
+    // Полный путь к иконке для фронтенда
+    for game in &mut games {
+        game.icon_url = game
+            .icon_path
+            .as_ref()
+            .map(|rel_path| app_data_dir.join(rel_path).to_string_lossy().into_owned());
+    }
+    Ok(games)
+}
+
+// Игры вместе с их rowid - короткими номерами, по которым к игре можно обратиться из CLI
+pub fn list_game_ids() -> Result<Vec<(i64, String)>, String> {
+    with_db(|conn| {
+        let mut stmt = conn.prepare("SELECT rowid, path FROM games ORDER BY rowid")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
     })
 }
 
+// Находит игру по номеру (rowid), пути к файлу или точному имени.
+// Имя подходит, только если оно не повторяется в библиотеке.
+pub fn resolve_game(query: &str) -> Result<GameEntry, String> {
+    if let Some(game) = find_game(query)? {
+        return Ok(game);
+    }
+    let path: Option<String> = with_db(|conn| {
+        if let Ok(rowid) = query.parse::<i64>() {
+            let path = conn
+                .query_row("SELECT path FROM games WHERE rowid = ?1", [rowid], |row| row.get(0))
+                .optional()?;
+            if path.is_some() {
+                return Ok(path);
+            }
+        }
+        let mut stmt = conn.prepare("SELECT path FROM games WHERE name = ?1 COLLATE NOCASE")?;
+        let paths = stmt
+            .query_map([query], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(if paths.len() == 1 { paths.into_iter().next() } else { None })
+    })?;
+    match path {
+        Some(path) => find_game(&path)?.ok_or_else(|| format!("Игра не найдена в библиотеке: {}", query)),
+        None => Err(format!("Игра не найдена в библиотеке: {}", query)),
+    }
+}
+
 
 #[tauri::command]
 pub fn db_update_game_playtime(path: String, session_seconds: i64) -> Result<(), String> {
+    // Время, добавленное вручную, тоже записываем как завершенную сессию,
+    // иначе оно потеряется при следующем пересчете
+    let ended_at = chrono::Local::now();
+    let started_at = ended_at - chrono::Duration::seconds(session_seconds);
     with_db(|conn| {
-        // Увеличиваем общее время на время последней сессии и обновляем дату
         conn.execute(
-            "UPDATE games SET play_time_seconds = play_time_seconds + ?2, last_played = strftime('%Y-%m-%d %H:%M:%S', 'now', 'localtime') WHERE path = ?1",
-            (path, session_seconds),
+            "INSERT INTO play_sessions (game_path, started_at, ended_at, duration_seconds, game_version)
+             VALUES (?1, ?2, ?3, ?4, (SELECT version FROM games WHERE path = ?1))",
+            (
+                &path,
+                started_at.format(DATETIME_FORMAT).to_string(),
+                ended_at.format(DATETIME_FORMAT).to_string(),
+                session_seconds,
+            ),
         )?;
-        Ok(())
+        recalculate_playtime(conn, &path)
     })
 }
 
-#[tauri::command]
-pub fn db_toggle_game_hidden(path: String, is_hidden: bool) -> Result<(), String> {
+// Общее время и дата последней игры всегда выводятся из истории сессий
+fn recalculate_playtime(conn: &Connection, game_path: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE games SET
+            play_time_seconds = (SELECT COALESCE(SUM(duration_seconds), 0) FROM play_sessions WHERE game_path = ?1 AND interrupted = FALSE),
+            last_played = (SELECT MAX(COALESCE(ended_at, started_at)) FROM play_sessions WHERE game_path = ?1 AND interrupted = FALSE)
+         WHERE path = ?1",
+        [game_path],
+    )?;
+    Ok(())
+}
+
+// Открывает новую сессию при запуске игры и возвращает ее id
+pub fn start_play_session(game_path: &str, game_version: Option<&str>) -> Result<i64, String> {
     with_db(|conn| {
         conn.execute(
-            "UPDATE games SET is_hidden = ?2 WHERE path = ?1",
-            (path, is_hidden),
+            "INSERT INTO play_sessions (game_path, started_at, game_version, tracker_pid)
+             VALUES (?1, strftime('%Y-%m-%d %H:%M:%S', 'now', 'localtime'), ?2, ?3)",
+            (game_path, game_version, std::process::id()),
         )?;
-        Ok(())
+        Ok(conn.last_insert_rowid())
     })
 }
 
-#[tauri::command]
-pub fn db_update_game_rating(path: String, rating: i32) -> Result<(), String> {
+// Отмечает сессию как закончившуюся падением
+pub fn record_session_crash(session_id: i64, crash: &SessionCrash) -> Result<(), String> {
     with_db(|conn| {
         conn.execute(
-            "UPDATE games SET rating = ?2 WHERE path = ?1",
-            (path, rating),
+            "UPDATE play_sessions SET crash_report = ?2, crash_exception = ?3, crash_script_file = ?4, crash_script_line = ?5 WHERE id = ?1",
+            (
+                session_id,
+                &crash.report_path,
+                &crash.exception,
+                &crash.script_file,
+                crash.script_line,
+            ),
         )?;
         Ok(())
     })
 }
 
-#[tauri::command]
-pub fn db_update_game_description(path: String, description: String) -> Result<(), String> {
+// Закрывает сессию после выхода из игры и пересчитывает общее время
+pub fn finish_play_session(
+    session_id: i64,
+    duration_seconds: i64,
+    exit_code: Option<i32>,
+) -> Result<(), String> {
     with_db(|conn| {
         conn.execute(
-            "UPDATE games SET description = ?2 WHERE path = ?1",
-            (path, description),
+            "UPDATE play_sessions SET ended_at = strftime('%Y-%m-%d %H:%M:%S', 'now', 'localtime'), duration_seconds = ?2, exit_code = ?3 WHERE id = ?1",
+            (session_id, duration_seconds, exit_code),
         )?;
-        Ok(())
+        let game_path: String = conn.query_row(
+            "SELECT game_path FROM play_sessions WHERE id = ?1",
+            [session_id],
+            |row| row.get(0),
+        )?;
+        recalculate_playtime(conn, &game_path)
     })
 }
 
-#[tauri::command]
-pub fn db_update_game_icon(path: String, icon_path: String) -> Result<(), String> {
+// Периодически сохраняет прогресс активной сессии, чтобы после сбоя
+// можно было закрыть ее на последней контрольной точке
+pub fn checkpoint_play_session(session_id: i64, duration_seconds: i64) -> Result<(), String> {
     with_db(|conn| {
         conn.execute(
-            "UPDATE games SET icon_path = ?2 WHERE path = ?1",
-            (path, icon_path),
+            "UPDATE play_sessions SET last_checkpoint = strftime('%Y-%m-%d %H:%M:%S', 'now', 'localtime'), duration_seconds = ?2 WHERE id = ?1 AND ended_at IS NULL",
+            (session_id, duration_seconds),
         )?;
         Ok(())
     })
 }
 
+// Жив ли еще процесс, который вел сессию. Процесс, запущенный позже сессии, - уже другой
+// процесс с тем же PID. Наш собственный PID тоже означает переиспользование: при старте
+// своих сессий у нас еще нет.
+fn session_tracker_alive(tracker_pid: u32, started_at: &str) -> bool {
+    if tracker_pid == std::process::id() {
+        return false;
+    }
+    let started_at = chrono::NaiveDateTime::parse_from_str(started_at, DATETIME_FORMAT)
+        .ok()
+        .and_then(|time| time.and_local_timezone(chrono::Local).earliest())
+        .map(|time| time.timestamp().max(0) as u64);
+    match started_at {
+        Some(started_at) => process_tree::is_alive_since(tracker_pid, started_at),
+        None => process_tree::is_alive_since(tracker_pid, u64::MAX),
+    }
+}
+
+// Вызывается при старте. Сессия оборвана, если процесс лаунчера, который ее вел, уже не работает,
+// а у старых сессий без tracker_pid - если контрольная точка давно не обновлялась.
+// В режиме "close" оборванные сессии сразу закрываются на последней контрольной точке,
+// в режиме "ask" помечаются и ждут решения пользователя.
+fn recover_interrupted_sessions(conn: &Connection) -> Result<()> {
+    let mode: String = conn
+        .query_row(
+            "SELECT value FROM settings WHERE key = 'interruptedSessions'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap_or_else(|_| "close".to_string());
+
+    let open_sessions = conn
+        .prepare(&format!(
+            "SELECT id, game_path, tracker_pid, started_at,
+                COALESCE(last_checkpoint, started_at) < datetime('now', 'localtime', '-{} minutes')
+             FROM play_sessions WHERE ended_at IS NULL AND interrupted = FALSE",
+            STALE_SESSION_MINUTES
+        ))?
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<u32>>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, bool>(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut game_paths = BTreeSet::new();
+    for (id, game_path, tracker_pid, started_at, is_stale) in open_sessions {
+        let interrupted = match tracker_pid {
+            Some(tracker_pid) => !session_tracker_alive(tracker_pid, &started_at),
+            None => is_stale,
+        };
+        if !interrupted {
+            continue;
+        }
+        if mode == "ask" {
+            conn.execute("UPDATE play_sessions SET interrupted = TRUE WHERE id = ?1", [id])?;
+        } else {
+            conn.execute(
+                "UPDATE play_sessions SET ended_at = COALESCE(last_checkpoint, started_at) WHERE id = ?1",
+                [id],
+            )?;
+        }
+        game_paths.insert(game_path);
+    }
+    if game_paths.is_empty() {
+        return Ok(());
+    }
+    if mode == "ask" {
+        eprintln!("Found interrupted sessions for {} game(s), waiting for user decision.", game_paths.len());
+    } else {
+        eprintln!("Closed interrupted sessions for {} game(s) at their last checkpoint.", game_paths.len());
+    }
+
+    for game_path in game_paths {
+        recalculate_playtime(conn, &game_path)?;
+    }
+    Ok(())
+}
+
+// Сессии, которые были оборваны и ждут решения, оставить их или удалить
 #[tauri::command]
-pub fn db_add_game(game: GameEntry) -> Result<(), String> {
+pub fn db_get_interrupted_sessions() -> Result<Vec<PlaySession>, String> {
     with_db(|conn| {
-        conn.execute(
-            // Добавляем версию при вставке
-            "INSERT OR IGNORE INTO games (path, name, game_type, version) VALUES (?1, ?2, ?3, ?4)",
-            (&game.path, &game.name, &game.game_type, &game.version),
-        )?;
-        Ok(())
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM play_sessions WHERE interrupted = TRUE ORDER BY started_at DESC",
+            PLAY_SESSION_COLUMNS
+        ))?;
+        let sessions = stmt.query_map([], map_play_session)?;
+        sessions.collect()
     })
 }
 
 #[tauri::command]
-pub fn db_update_game_version(path: String, version: String) -> Result<(), String> {
+pub fn db_resolve_interrupted_session(id: i64, keep: bool) -> Result<(), String> {
     with_db(|conn| {
-        conn.execute(
-            "UPDATE games SET version = ?2 WHERE path = ?1",
-            (path, version),
+        let game_path: String = conn.query_row(
+            "SELECT game_path FROM play_sessions WHERE id = ?1 AND interrupted = TRUE",
+            [id],
+            |row| row.get(0),
+        )?;
+        if keep {
+            // Оставляем время до последней контрольной точки
+            conn.execute(
+                "UPDATE play_sessions SET ended_at = COALESCE(last_checkpoint, started_at), interrupted = FALSE WHERE id = ?1",
+                [id],
+            )?;
+        } else {
+            conn.execute("DELETE FROM play_sessions WHERE id = ?1", [id])?;
+        }
+        recalculate_playtime(conn, &game_path)
+    })
+}
+
+fn map_play_session(row: &rusqlite::Row) -> Result<PlaySession> {
+    Ok(PlaySession {
+        id: row.get(0)?,
+        game_path: row.get(1)?,
+        started_at: row.get(2)?,
+        ended_at: row.get(3)?,
+        duration_seconds: row.get(4)?,
+        exit_code: row.get(5)?,
+        game_version: row.get(6)?,
+        last_checkpoint: row.get(7)?,
+        interrupted: row.get(8)?,
+        crash: match row.get::<_, Option<String>>(9)? {
+            Some(report_path) => Some(SessionCrash {
+                report_path,
+                exception: row.get(10)?,
+                script_file: row.get(11)?,
+                script_line: row.get(12)?,
+            }),
+            None => None,
+        },
+    })
+}
+
+#[tauri::command]
+pub fn db_get_play_sessions(game_path: String) -> Result<Vec<PlaySession>, String> {
+    with_db(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM play_sessions WHERE game_path = ?1 ORDER BY started_at DESC",
+            PLAY_SESSION_COLUMNS
+        ))?;
+        let sessions = stmt.query_map([game_path], map_play_session)?;
+        sessions.collect()
+    })
+}
+
+// Правка сессии, например если игру забыли закрыть на ночь.
+// Если длительность не передана, она считается по времени начала и конца.
+#[tauri::command]
+pub fn db_update_play_session(
+    id: i64,
+    started_at: String,
+    ended_at: String,
+    duration_seconds: Option<i64>,
+) -> Result<(), String> {
+    let start = chrono::NaiveDateTime::parse_from_str(&started_at, DATETIME_FORMAT)
+        .map_err(|e| format!("Некорректное время начала: {}", e))?;
+    let end = chrono::NaiveDateTime::parse_from_str(&ended_at, DATETIME_FORMAT)
+        .map_err(|e| format!("Некорректное время окончания: {}", e))?;
+    if end < start {
+        return Err("Сессия не может закончиться раньше, чем началась".to_string());
+    }
+    let duration = duration_seconds.unwrap_or_else(|| (end - start).num_seconds());
+
+    with_db(|conn| {
+        conn.execute(
+            "UPDATE play_sessions SET started_at = ?2, ended_at = ?3, duration_seconds = ?4 WHERE id = ?1",
+            (id, &started_at, &ended_at, duration.max(0)),
+        )?;
+        let game_path: String = conn.query_row(
+            "SELECT game_path FROM play_sessions WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        )?;
+        recalculate_playtime(conn, &game_path)
+    })
+}
+
+#[tauri::command]
+pub fn db_delete_play_session(id: i64) -> Result<(), String> {
+    with_db(|conn| {
+        let game_path: String = conn.query_row(
+            "SELECT game_path FROM play_sessions WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        )?;
+        conn.execute("DELETE FROM play_sessions WHERE id = ?1", [id])?;
+        recalculate_playtime(conn, &game_path)
+    })
+}
+
+// Условие "все установленные версии названия игры ?1"
+const SAME_TITLE: &str = "(path = ?1 OR title_key = (SELECT title_key FROM games WHERE path = ?1))";
+
+// Строка titles для названия, которое стало объединять несколько версий.
+// Начальные данные берутся у текущей версии; строки самих версий не меняются.
+fn ensure_title_row(conn: &Connection, title_key: &str) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO titles (title_key, name, name_source, rating, description, is_hidden, completion_percent)
+         SELECT title_key, name, name_source, rating, description, is_hidden, completion_percent
+         FROM games WHERE title_key = ?1 ORDER BY is_current_version DESC LIMIT 1",
+        [title_key],
+    )?;
+    Ok(())
+}
+
+// Название, у которого не осталось версий, больше не нужно
+fn drop_orphan_title(conn: &Connection, title_key: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM titles WHERE title_key = ?1 AND NOT EXISTS (SELECT 1 FROM games WHERE title_key = ?1)",
+        [title_key],
+    )?;
+    Ok(())
+}
+
+// Оценка, заметки, имя, скрытие и прогресс относятся к названию целиком: у объединенных версий
+// они меняются в titles, у одиночной игры - в ее строке games. assignments - "rating = ?2" и т.п.
+fn update_title_data(conn: &Connection, path: &str, assignments: &str, value: &dyn rusqlite::ToSql) -> Result<()> {
+    let updated = conn.execute(
+        &format!(
+            "UPDATE titles SET {} WHERE title_key = (SELECT title_key FROM games WHERE path = ?1)",
+            assignments
+        ),
+        rusqlite::params![path, value],
+    )?;
+    if updated == 0 {
+        conn.execute(
+            &format!("UPDATE games SET {} WHERE path = ?1", assignments),
+            rusqlite::params![path, value],
+        )?;
+    }
+    Ok(())
+}
+
+fn set_current_version(conn: &Connection, path: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE games SET is_current_version = (path = ?1) WHERE title_key = (SELECT title_key FROM games WHERE path = ?1)",
+        [path],
+    )?;
+    Ok(())
+}
+
+// Если у названия не осталось текущей версии (ее удалили или отвязали), текущей становится самая новая
+fn ensure_current_version(conn: &Connection, title_key: &str) -> Result<()> {
+    let versions = conn
+        .prepare("SELECT path, version, is_current_version FROM games WHERE title_key = ?1")?
+        .query_map([title_key], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, bool>(2)?))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    if versions.iter().any(|(_, _, is_current)| *is_current) {
+        return Ok(());
+    }
+    match versions
+        .iter()
+        .max_by(|a, b| titles::compare_versions(a.1.as_deref(), b.1.as_deref()))
+    {
+        Some((path, _, _)) => set_current_version(conn, path),
+        None => Ok(()),
+    }
+}
+
+// Новая игра с тем же ключом названия, что у уже добавленной, становится еще одной ее версией.
+// Текущей она становится, только если ее версия новее.
+fn join_title(conn: &Connection, game: &GameEntry) -> Result<()> {
+    let key = titles::title_key(&game.path, &game.name, game.name_source.as_deref());
+    let current: Option<(String, Option<String>)> = conn
+        .query_row(
+            "SELECT path, version FROM games WHERE title_key = ?1 AND is_current_version AND path != ?2",
+            (&key, &game.path),
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    if let Some((current_path, current_version)) = &current {
+        // Пока игра не присоединена, данные названия - это данные текущей версии
+        ensure_title_row(conn, &key)?;
+        conn.execute(
+            "UPDATE games SET title_key = ?2, is_current_version = FALSE WHERE path = ?1",
+            (&game.path, &key),
+        )?;
+        if titles::compare_versions(game.version.as_deref(), current_version.as_deref()) == Ordering::Greater {
+            set_current_version(conn, &game.path)?;
+        } else {
+            set_current_version(conn, current_path)?;
+        }
+    } else {
+        conn.execute(
+            "UPDATE games SET title_key = ?2, is_current_version = TRUE WHERE path = ?1",
+            (&game.path, &key),
+        )?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn db_toggle_game_hidden(path: String, is_hidden: bool) -> Result<(), String> {
+    with_db(|conn| update_title_data(conn, &path, "is_hidden = ?2", &is_hidden))
+}
+
+#[tauri::command]
+pub fn db_update_game_rating(path: String, rating: i32) -> Result<(), String> {
+    with_db(|conn| update_title_data(conn, &path, "rating = ?2", &rating))
+}
+
+#[tauri::command]
+pub fn db_update_game_description(path: String, description: String) -> Result<(), String> {
+    with_db(|conn| update_title_data(conn, &path, "description = ?2", &description))
+}
+
+#[tauri::command]
+pub fn db_update_game_icon(path: String, icon_path: String) -> Result<(), String> {
+    with_db(|conn| {
+        conn.execute(
+            "UPDATE games SET icon_path = ?2 WHERE path = ?1",
+            (path, icon_path),
+        )?;
+        Ok(())
+    })
+}
+
+// Иконку из этого .exe уже пытались достать и не смогли. modified_at - время изменения
+// файла: после обновления игры попробуем еще раз.
+pub fn icon_failed(source_path: &str, modified_at: i64) -> Result<bool, String> {
+    with_db(|conn| {
+        conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM icon_failures WHERE source_path = ?1 AND modified_at = ?2)",
+            (source_path, modified_at),
+            |row| row.get(0),
+        )
+    })
+}
+
+pub fn record_icon_failure(source_path: &str, modified_at: i64) -> Result<(), String> {
+    with_db(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO icon_failures (source_path, modified_at) VALUES (?1, ?2)",
+            (source_path, modified_at),
         )?;
         Ok(())
     })
 }
 
 #[tauri::command]
-pub fn db_update_game_name(path: String, name: String) -> Result<(), String> {
+pub fn db_add_game(game: GameEntry) -> Result<(), String> {
     with_db(|conn| {
-        conn.execute("UPDATE games SET name = ?2 WHERE path = ?1", (path, name))?;
+        let is_new: bool = conn.query_row(
+            "SELECT NOT EXISTS(SELECT 1 FROM games WHERE path = ?1)",
+            [&game.path],
+            |row| row.get(0),
+        )?;
+        conn.execute(
+            // Добавляем версию, движок и метаданные при вставке. Для уже добавленной игры
+            // только дополняем то, что раньше определить не удалось; имя и версию не трогаем.
+            "INSERT INTO games (path, name, game_type, version, engine, name_source, version_source, save_directory, build_name, engine_version)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+             ON CONFLICT(path) DO UPDATE SET
+                engine = COALESCE(games.engine, excluded.engine),
+                save_directory = COALESCE(games.save_directory, excluded.save_directory),
+                build_name = COALESCE(games.build_name, excluded.build_name),
+                engine_version = COALESCE(games.engine_version, excluded.engine_version)",
+            (
+                &game.path,
+                &game.name,
+                &game.game_type,
+                &game.version,
+                &game.engine,
+                &game.name_source,
+                &game.version_source,
+                &game.save_directory,
+                &game.build_name,
+                &game.engine_version,
+            ),
+        )?;
+        if is_new {
+            join_title(conn, &game)?;
+        }
         Ok(())
     })
 }
 
+#[tauri::command]
+pub fn db_update_game_version(path: String, version: String) -> Result<(), String> {
+    with_db(|conn| {
+        conn.execute(
+            "UPDATE games SET version = ?2, version_source = 'user' WHERE path = ?1",
+            (path, version),
+        )?;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn db_update_game_name(path: String, name: String) -> Result<(), String> {
+    with_db(|conn| update_title_data(conn, &path, "name = ?2, name_source = 'user'", &name))
+}
+
 #[tauri::command]
 pub fn db_create_folder(name: String) -> Result<i64, String> {
     with_db(|conn| {
@@ -315,18 +1422,24 @@ pub fn db_add_game_to_folder(game_path: String, folder_id: i64) -> Result<(), St
 pub fn db_remove_game_from_folder(game_path: String, folder_id: i64) -> Result<(), String> {
     with_db(|conn| {
         conn.execute(
-            "DELETE FROM game_folders WHERE game_path = ?1 AND folder_id = ?2",
+            &format!(
+                "DELETE FROM game_folders WHERE folder_id = ?2 AND game_path IN (SELECT path FROM games WHERE {})",
+                SAME_TITLE
+            ),
             (game_path, folder_id),
         )?;
         Ok(())
     })
 }
 
-// Новая команда, чтобы узнать, в каких папках состоит игра
+// Папки, в которых лежит игра или любая другая версия ее названия
 #[tauri::command]
 pub fn db_get_folders_for_game(game_path: String) -> Result<Vec<i64>, String> {
     with_db(|conn| {
-        let mut stmt = conn.prepare("SELECT folder_id FROM game_folders WHERE game_path = ?1")?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT DISTINCT folder_id FROM game_folders WHERE game_path IN (SELECT path FROM games WHERE {})",
+            SAME_TITLE
+        ))?;
         let ids_iter = stmt.query_map([game_path], |row| row.get(0))?;
 
         let mut ids = Vec::new();
@@ -340,12 +1453,36 @@ pub fn db_get_folders_for_game(game_path: String) -> Result<Vec<i64>, String> {
 #[tauri::command]
 pub fn db_delete_game(game_path: String) -> Result<(), String> {
     with_db(|conn| {
+        let title_key: Option<String> = conn
+            .query_row("SELECT title_key FROM games WHERE path = ?1", [&game_path], |row| row.get(0))
+            .optional()?
+            .flatten();
         conn.execute("DELETE FROM games WHERE path = ?1", [game_path.clone()])?;
         // Также удаляем игру из всех папок, если она там была
         conn.execute(
             "DELETE FROM game_folders WHERE game_path = ?1",
             [game_path.clone()],
         )?;
+        conn.execute(
+            "DELETE FROM play_sessions WHERE game_path = ?1",
+            [game_path.clone()],
+        )?;
+        conn.execute(
+            "DELETE FROM launch_profiles WHERE game_path = ?1",
+            [game_path.clone()],
+        )?;
+        conn.execute(
+            "DELETE FROM game_hooks WHERE game_path = ?1",
+            [game_path.clone()],
+        )?;
+        conn.execute(
+            "DELETE FROM hook_runs WHERE game_path = ?1",
+            [game_path.clone()],
+        )?;
+        if let Some(title_key) = title_key {
+            ensure_current_version(conn, &title_key)?;
+            drop_orphan_title(conn, &title_key)?;
+        }
         Ok(())
     })
 }
@@ -377,11 +1514,605 @@ pub fn db_set_setting(key: String, value: String) -> Result<(), String> {
 
 #[tauri::command]
 pub fn db_update_game_completion(path: String, percent: i32) -> Result<(), String> {
+    with_db(|conn| update_title_data(conn, &path, "completion_percent = ?2", &percent))
+}
+
+fn map_launch_profile(row: &rusqlite::Row) -> Result<LaunchProfile> {
+    let arguments: String = row.get(3)?;
+    let env_vars: String = row.get(4)?;
+    Ok(LaunchProfile {
+        id: row.get(0)?,
+        game_path: row.get(1)?,
+        name: row.get(2)?,
+        arguments: serde_json::from_str(&arguments).unwrap_or_default(),
+        env_vars: serde_json::from_str(&env_vars).unwrap_or_default(),
+        working_dir: row.get(5)?,
+        wrapper: row.get(6)?,
+        is_default: row.get(7)?,
+    })
+}
+
+#[tauri::command]
+pub fn db_get_launch_profiles(game_path: String) -> Result<Vec<LaunchProfile>, String> {
+    with_db(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, game_path, name, arguments, env_vars, working_dir, wrapper, is_default
+             FROM launch_profiles WHERE game_path = ?1 ORDER BY is_default DESC, name",
+        )?;
+        let profiles = stmt.query_map([game_path], map_launch_profile)?;
+        profiles.collect()
+    })
+}
+
+// Профиль для запуска: явно выбранный или профиль игры по умолчанию
+pub fn find_launch_profile(game_path: &str, profile_id: Option<i64>) -> Result<Option<LaunchProfile>, String> {
+    with_db(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, game_path, name, arguments, env_vars, working_dir, wrapper, is_default
+             FROM launch_profiles WHERE game_path = ?1 AND (id = ?2 OR (?2 IS NULL AND is_default = TRUE))",
+        )?;
+        let mut rows = stmt.query_map((game_path, profile_id), map_launch_profile)?;
+        rows.next().transpose()
+    })
+}
+
+// Создает профиль (если id не задан) или обновляет существующий. Возвращает id профиля.
+#[tauri::command]
+pub fn db_save_launch_profile(profile: LaunchProfile) -> Result<i64, String> {
+    let arguments = serde_json::to_string(&profile.arguments).map_err(|e| e.to_string())?;
+    let env_vars = serde_json::to_string(&profile.env_vars).map_err(|e| e.to_string())?;
+    let working_dir = profile.working_dir.filter(|dir| !dir.trim().is_empty());
+    let wrapper = profile.wrapper.filter(|wrapper| !wrapper.trim().is_empty());
+
+    with_db(|conn| {
+        // Первый профиль игры автоматически становится профилем по умолчанию
+        let has_profiles: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM launch_profiles WHERE game_path = ?1 AND id IS NOT ?2)",
+            (&profile.game_path, profile.id),
+            |row| row.get(0),
+        )?;
+        let is_default = profile.is_default || !has_profiles;
+
+        let id = if let Some(id) = profile.id {
+            conn.execute(
+                "UPDATE launch_profiles SET name = ?2, arguments = ?3, env_vars = ?4, working_dir = ?5, wrapper = ?6, is_default = ?7 WHERE id = ?1",
+                (id, &profile.name, &arguments, &env_vars, &working_dir, &wrapper, is_default),
+            )?;
+            id
+        } else {
+            conn.execute(
+                "INSERT INTO launch_profiles (game_path, name, arguments, env_vars, working_dir, wrapper, is_default) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                (&profile.game_path, &profile.name, &arguments, &env_vars, &working_dir, &wrapper, is_default),
+            )?;
+            conn.last_insert_rowid()
+        };
+
+        if is_default {
+            conn.execute(
+                "UPDATE launch_profiles SET is_default = FALSE WHERE game_path = ?1 AND id != ?2",
+                (&profile.game_path, id),
+            )?;
+        }
+        Ok(id)
+    })
+}
+
+#[tauri::command]
+pub fn db_delete_launch_profile(id: i64) -> Result<(), String> {
+    with_db(|conn| {
+        conn.execute("DELETE FROM launch_profiles WHERE id = ?1", [id])?;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn db_set_default_launch_profile(game_path: String, id: i64) -> Result<(), String> {
     with_db(|conn| {
         conn.execute(
-            "UPDATE games SET completion_percent = ?2 WHERE path = ?1",
-            (path, percent),
+            "UPDATE launch_profiles SET is_default = (id = ?2) WHERE game_path = ?1",
+            (game_path, id),
         )?;
         Ok(())
     })
-}
\ No newline at end of file
+}
+
+fn map_runner(row: &rusqlite::Row) -> Result<Runner> {
+    Ok(Runner {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        kind: row.get(2)?,
+        path: row.get(3)?,
+        is_default: row.get(4)?,
+    })
+}
+
+#[tauri::command]
+pub fn db_get_runners() -> Result<Vec<Runner>, String> {
+    with_db(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, name, kind, path, is_default FROM runners ORDER BY is_default DESC, name",
+        )?;
+        let runners = stmt.query_map([], map_runner)?;
+        runners.collect()
+    })
+}
+
+#[tauri::command]
+pub fn db_save_runner(runner: Runner) -> Result<i64, String> {
+    if runner.kind != "wine" && runner.kind != "proton" {
+        return Err(format!("Неизвестный тип раннера: {}", runner.kind));
+    }
+    with_db(|conn| {
+        let has_runners: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM runners WHERE id IS NOT ?1)",
+            [runner.id],
+            |row| row.get(0),
+        )?;
+        let is_default = runner.is_default || !has_runners;
+
+        let id = if let Some(id) = runner.id {
+            conn.execute(
+                "UPDATE runners SET name = ?2, kind = ?3, path = ?4, is_default = ?5 WHERE id = ?1",
+                (id, &runner.name, &runner.kind, &runner.path, is_default),
+            )?;
+            id
+        } else {
+            conn.execute(
+                "INSERT INTO runners (name, kind, path, is_default) VALUES (?1, ?2, ?3, ?4)",
+                (&runner.name, &runner.kind, &runner.path, is_default),
+            )?;
+            conn.last_insert_rowid()
+        };
+
+        if is_default {
+            conn.execute("UPDATE runners SET is_default = FALSE WHERE id != ?1", [id])?;
+        }
+        Ok(id)
+    })
+}
+
+#[tauri::command]
+pub fn db_delete_runner(id: i64) -> Result<(), String> {
+    with_db(|conn| {
+        conn.execute("DELETE FROM runners WHERE id = ?1", [id])?;
+        // Игры с этим раннером вернутся к раннеру по умолчанию
+        conn.execute("UPDATE games SET runner_id = NULL WHERE runner_id = ?1", [id])?;
+        Ok(())
+    })
+}
+
+// runner_id = None - использовать раннер по умолчанию.
+// prefix_mode: "shared" (общий префикс), "game" (свой префикс у игры) или None (как в настройках).
+#[tauri::command]
+pub fn db_set_game_runner(
+    game_path: String,
+    runner_id: Option<i64>,
+    prefix_mode: Option<String>,
+) -> Result<(), String> {
+    if let Some(mode) = &prefix_mode {
+        if mode != "shared" && mode != "game" {
+            return Err(format!("Неизвестный режим префикса: {}", mode));
+        }
+    }
+    with_db(|conn| {
+        conn.execute(
+            "UPDATE games SET runner_id = ?2, wine_prefix_mode = ?3 WHERE path = ?1",
+            (game_path, runner_id, prefix_mode),
+        )?;
+        Ok(())
+    })
+}
+
+// Раннер и режим префикса для игры с учетом значений по умолчанию
+pub fn find_game_runner(game_path: &str) -> Result<(Option<Runner>, String), String> {
+    with_db(|conn| {
+        let (runner_id, prefix_mode): (Option<i64>, Option<String>) = conn.query_row(
+            "SELECT runner_id, wine_prefix_mode FROM games WHERE path = ?1",
+            [game_path],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, name, kind, path, is_default FROM runners
+             WHERE id = ?1 OR (?1 IS NULL AND is_default = TRUE)",
+        )?;
+        let runner = stmt.query_map([runner_id], map_runner)?.next().transpose()?;
+
+        let prefix_mode = match prefix_mode {
+            Some(mode) => mode,
+            None => conn
+                .query_row(
+                    "SELECT value FROM settings WHERE key = 'winePrefixMode'",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap_or_else(|_| "shared".to_string()),
+        };
+        Ok((runner, prefix_mode))
+    })
+}
+
+const GAME_HOOK_COLUMNS: &str =
+    "id, game_path, stage, command, timeout_seconds, abort_on_failure, enabled, sort_order";
+
+fn map_game_hook(row: &rusqlite::Row) -> Result<GameHook> {
+    Ok(GameHook {
+        id: row.get(0)?,
+        game_path: row.get(1)?,
+        stage: row.get(2)?,
+        command: row.get(3)?,
+        timeout_seconds: row.get(4)?,
+        abort_on_failure: row.get(5)?,
+        enabled: row.get(6)?,
+        sort_order: row.get(7)?,
+    })
+}
+
+// game_path = None - список глобальных хуков
+#[tauri::command]
+pub fn db_get_hooks(game_path: Option<String>) -> Result<Vec<GameHook>, String> {
+    with_db(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM game_hooks WHERE game_path IS ?1 ORDER BY stage, sort_order, id",
+            GAME_HOOK_COLUMNS
+        ))?;
+        let hooks = stmt.query_map([game_path], map_game_hook)?;
+        hooks.collect()
+    })
+}
+
+// Включенные хуки для запуска: сначала глобальные, затем хуки самой игры
+pub fn find_hooks(game_path: &str, stage: &str) -> Result<Vec<GameHook>, String> {
+    with_db(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM game_hooks
+             WHERE stage = ?2 AND enabled = TRUE AND (game_path IS NULL OR game_path = ?1)
+             ORDER BY game_path IS NOT NULL, sort_order, id",
+            GAME_HOOK_COLUMNS
+        ))?;
+        let hooks = stmt.query_map((game_path, stage), map_game_hook)?;
+        hooks.collect()
+    })
+}
+
+#[tauri::command]
+pub fn db_save_hook(hook: GameHook) -> Result<i64, String> {
+    if hook.stage != "pre_launch" && hook.stage != "post_exit" {
+        return Err(format!("Неизвестный этап хука: {}", hook.stage));
+    }
+    if hook.command.trim().is_empty() {
+        return Err("Команда хука не может быть пустой".to_string());
+    }
+    let timeout_seconds = hook.timeout_seconds.max(1);
+    with_db(|conn| {
+        if let Some(id) = hook.id {
+            conn.execute(
+                "UPDATE game_hooks SET game_path = ?2, stage = ?3, command = ?4, timeout_seconds = ?5, abort_on_failure = ?6, enabled = ?7, sort_order = ?8 WHERE id = ?1",
+                (id, &hook.game_path, &hook.stage, &hook.command, timeout_seconds, hook.abort_on_failure, hook.enabled, hook.sort_order),
+            )?;
+            Ok(id)
+        } else {
+            conn.execute(
+                "INSERT INTO game_hooks (game_path, stage, command, timeout_seconds, abort_on_failure, enabled, sort_order) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                (&hook.game_path, &hook.stage, &hook.command, timeout_seconds, hook.abort_on_failure, hook.enabled, hook.sort_order),
+            )?;
+            Ok(conn.last_insert_rowid())
+        }
+    })
+}
+
+#[tauri::command]
+pub fn db_delete_hook(id: i64) -> Result<(), String> {
+    with_db(|conn| {
+        conn.execute("DELETE FROM game_hooks WHERE id = ?1", [id])?;
+        Ok(())
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn record_hook_run(
+    hook_id: i64,
+    game_path: &str,
+    session_id: Option<i64>,
+    stage: &str,
+    duration_ms: i64,
+    exit_code: Option<i32>,
+    timed_out: bool,
+    output: &str,
+) -> Result<(), String> {
+    with_db(|conn| {
+        conn.execute(
+            "INSERT INTO hook_runs (hook_id, game_path, session_id, stage, started_at, duration_ms, exit_code, timed_out, output)
+             VALUES (?1, ?2, ?3, ?4, datetime('now', 'localtime', '-' || (?5 / 1000) || ' seconds'), ?5, ?6, ?7, ?8)",
+            (hook_id, game_path, session_id, stage, duration_ms, exit_code, timed_out, output),
+        )?;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn db_get_hook_runs(game_path: String, limit: Option<i64>) -> Result<Vec<HookRun>, String> {
+    with_db(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, hook_id, game_path, session_id, stage, started_at, duration_ms, exit_code, timed_out, output
+             FROM hook_runs WHERE game_path = ?1 ORDER BY id DESC LIMIT ?2",
+        )?;
+        let runs = stmt.query_map((game_path, limit.unwrap_or(50)), |row| {
+            Ok(HookRun {
+                id: row.get(0)?,
+                hook_id: row.get(1)?,
+                game_path: row.get(2)?,
+                session_id: row.get(3)?,
+                stage: row.get(4)?,
+                started_at: row.get(5)?,
+                duration_ms: row.get(6)?,
+                exit_code: row.get(7)?,
+                timed_out: row.get(8)?,
+                output: row.get(9)?,
+            })
+        })?;
+        runs.collect()
+    })
+}
+
+// enabled = None - следовать глобальной настройке autoBackupOnExit
+#[tauri::command]
+pub fn db_set_game_auto_backup(game_path: String, enabled: Option<bool>) -> Result<(), String> {
+    with_db(|conn| {
+        conn.execute(
+            "UPDATE games SET auto_backup = ?2 WHERE path = ?1",
+            (game_path, enabled),
+        )?;
+        Ok(())
+    })
+}
+
+pub fn auto_backup_enabled(game_path: &str) -> Result<bool, String> {
+    with_db(|conn| {
+        conn.query_row(
+            "SELECT COALESCE(g.auto_backup, (SELECT value = 'true' FROM settings WHERE key = 'autoBackupOnExit'), FALSE)
+             FROM games g WHERE g.path = ?1",
+            [game_path],
+            |row| row.get(0),
+        )
+    })
+}
+
+pub fn backup_fingerprint(game_path: &str) -> Result<Option<String>, String> {
+    with_db(|conn| {
+        conn.query_row(
+            "SELECT backup_fingerprint FROM games WHERE path = ?1",
+            [game_path],
+            |row| row.get(0),
+        )
+    })
+}
+
+pub fn set_backup_fingerprint(game_path: &str, fingerprint: &str) -> Result<(), String> {
+    with_db(|conn| {
+        conn.execute(
+            "UPDATE games SET backup_fingerprint = ?2 WHERE path = ?1",
+            (game_path, fingerprint),
+        )?;
+        Ok(())
+    })
+}
+
+fn map_library_root(row: &rusqlite::Row) -> Result<LibraryRoot> {
+    let dir_blacklist: String = row.get(3)?;
+    let file_blacklist: String = row.get(4)?;
+    Ok(LibraryRoot {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        deep_search: row.get(2)?,
+        dir_blacklist: serde_json::from_str(&dir_blacklist).unwrap_or_default(),
+        file_blacklist: serde_json::from_str(&file_blacklist).unwrap_or_default(),
+        last_scan_at: row.get(5)?,
+    })
+}
+
+#[tauri::command]
+pub fn db_get_library_roots() -> Result<Vec<LibraryRoot>, String> {
+    with_db(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, path, deep_search, dir_blacklist, file_blacklist, last_scan_at FROM library_roots ORDER BY path",
+        )?;
+        let roots = stmt.query_map([], map_library_root)?;
+        roots.collect()
+    })
+}
+
+#[tauri::command]
+pub fn db_save_library_root(root: LibraryRoot) -> Result<i64, String> {
+    // Черные списки сравниваются с именами в нижнем регистре
+    let lowercase_json = |list: &[String]| {
+        let list: Vec<String> = list.iter().map(|item| item.trim().to_lowercase()).collect();
+        serde_json::to_string(&list).map_err(|e| e.to_string())
+    };
+    let dir_blacklist = lowercase_json(&root.dir_blacklist)?;
+    let file_blacklist = lowercase_json(&root.file_blacklist)?;
+    with_db(|conn| {
+        if let Some(id) = root.id {
+            conn.execute(
+                "UPDATE library_roots SET path = ?2, deep_search = ?3, dir_blacklist = ?4, file_blacklist = ?5 WHERE id = ?1",
+                (id, &root.path, root.deep_search, &dir_blacklist, &file_blacklist),
+            )?;
+            Ok(id)
+        } else {
+            conn.execute(
+                "INSERT INTO library_roots (path, deep_search, dir_blacklist, file_blacklist) VALUES (?1, ?2, ?3, ?4)",
+                (&root.path, root.deep_search, &dir_blacklist, &file_blacklist),
+            )?;
+            Ok(conn.last_insert_rowid())
+        }
+    })
+}
+
+#[tauri::command]
+pub fn db_delete_library_root(id: i64) -> Result<(), String> {
+    with_db(|conn| {
+        conn.execute("DELETE FROM library_roots WHERE id = ?1", [id])?;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn db_get_scan_rules() -> Result<Vec<ScanRule>, String> {
+    with_db(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, target, kind, pattern, enabled, is_builtin FROM scan_rules ORDER BY target, id",
+        )?;
+        let rules = stmt.query_map([], |row| {
+            Ok(ScanRule {
+                id: row.get(0)?,
+                target: row.get(1)?,
+                kind: row.get(2)?,
+                pattern: row.get(3)?,
+                enabled: row.get(4)?,
+                is_builtin: row.get(5)?,
+            })
+        })?;
+        rules.collect()
+    })
+}
+
+#[tauri::command]
+pub fn db_save_scan_rule(rule: ScanRule) -> Result<i64, String> {
+    if rule.target != "dir" && rule.target != "file" {
+        return Err(format!("Неизвестная цель правила: {}", rule.target));
+    }
+    scan_rules::validate(&rule.kind, &rule.pattern)?;
+    let pattern = rule.pattern.trim();
+    with_db(|conn| {
+        if let Some(id) = rule.id {
+            // is_builtin не меняется: измененное встроенное правило остается встроенным
+            conn.execute(
+                "UPDATE scan_rules SET target = ?2, kind = ?3, pattern = ?4, enabled = ?5 WHERE id = ?1",
+                (id, &rule.target, &rule.kind, pattern, rule.enabled),
+            )?;
+            Ok(id)
+        } else {
+            conn.execute(
+                "INSERT INTO scan_rules (target, kind, pattern, enabled) VALUES (?1, ?2, ?3, ?4)",
+                (&rule.target, &rule.kind, pattern, rule.enabled),
+            )?;
+            Ok(conn.last_insert_rowid())
+        }
+    })
+}
+
+#[tauri::command]
+pub fn db_delete_scan_rule(id: i64) -> Result<(), String> {
+    with_db(|conn| {
+        conn.execute("DELETE FROM scan_rules WHERE id = ?1", [id])?;
+        Ok(())
+    })
+}
+
+// Возвращает встроенные правила к исходному виду. Правила пользователя не трогает.
+#[tauri::command]
+pub fn db_reset_scan_rules() -> Result<(), String> {
+    with_db(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM scan_rules WHERE is_builtin = TRUE", [])?;
+        insert_builtin_scan_rules(&tx)?;
+        tx.commit()
+    })
+}
+
+pub fn set_library_root_scanned(id: i64) -> Result<(), String> {
+    with_db(|conn| {
+        conn.execute(
+            "UPDATE library_roots SET last_scan_at = strftime('%Y-%m-%d %H:%M:%S', 'now', 'localtime') WHERE id = ?1",
+            [id],
+        )?;
+        Ok(())
+    })
+}
+
+// Переносит игру на новый путь вместе со всем, что к ней привязано:
+// папками, сессиями, профилями запуска и хуками
+pub fn move_game_path(old_path: &str, new_path: &str) -> Result<(), String> {
+    with_db(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        // Внешние ключи на games(path) проверяем в конце транзакции, когда обновлены все таблицы
+        tx.execute_batch("PRAGMA defer_foreign_keys = ON;")?;
+        tx.execute("UPDATE games SET path = ?2 WHERE path = ?1", (old_path, new_path))?;
+        for table in ["game_folders", "play_sessions", "launch_profiles", "game_hooks", "hook_runs"] {
+            tx.execute(
+                &format!("UPDATE {} SET game_path = ?2 WHERE game_path = ?1", table),
+                (old_path, new_path),
+            )?;
+        }
+        tx.commit()
+    })
+}
+
+// Все установленные версии названия игры, от новой к старой
+#[tauri::command]
+pub fn db_get_game_versions(game_path: String) -> Result<Vec<GameEntry>, String> {
+    let mut versions = with_db(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM {} WHERE g.path = ?1 OR g.title_key = (SELECT title_key FROM games WHERE path = ?1)",
+            GAME_COLUMNS, GAME_TABLES
+        ))?;
+        let versions = stmt.query_map([&game_path], map_game)?;
+        versions.collect::<Result<Vec<GameEntry>>>()
+    })?;
+    versions.sort_by(|a, b| titles::compare_versions(b.version.as_deref(), a.version.as_deref()));
+    Ok(versions)
+}
+
+// Делает версию текущей: ее показывает библиотека и запускает кнопка "Играть"
+#[tauri::command]
+pub fn db_set_current_version(game_path: String) -> Result<(), String> {
+    with_db(|conn| set_current_version(conn, &game_path))
+}
+
+// Присоединяет все версии игры game_path к названию title_path, если сканер не узнал в них одну игру.
+// Текущей остается текущая версия title_path, оценка и заметки берутся у названия title_path.
+#[tauri::command]
+pub fn db_link_game_version(game_path: String, title_path: String) -> Result<(), String> {
+    with_db(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let title_key: String =
+            tx.query_row("SELECT title_key FROM games WHERE path = ?1", [&title_path], |row| row.get(0))?;
+        let old_key: Option<String> = tx
+            .query_row("SELECT title_key FROM games WHERE path = ?1", [&game_path], |row| row.get(0))
+            .optional()?
+            .flatten();
+        ensure_title_row(&tx, &title_key)?;
+        tx.execute(
+            &format!("UPDATE games SET title_key = ?2, is_current_version = FALSE WHERE {}", SAME_TITLE),
+            (&game_path, &title_key),
+        )?;
+        // Игру присоединили к самой себе
+        ensure_current_version(&tx, &title_key)?;
+        if let Some(old_key) = old_key {
+            drop_orphan_title(&tx, &old_key)?;
+        }
+        tx.commit()
+    })
+}
+
+// Отделяет версию в самостоятельную игру. У нее снова собственные имя, оценка и заметки,
+// какими они были до объединения.
+#[tauri::command]
+pub fn db_unlink_game_version(game_path: String) -> Result<(), String> {
+    with_db(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let title_key: Option<String> = tx
+            .query_row("SELECT title_key FROM games WHERE path = ?1", [&game_path], |row| row.get(0))
+            .optional()?
+            .flatten();
+        tx.execute(
+            "UPDATE games SET title_key = ?2, is_current_version = TRUE WHERE path = ?1",
+            (&game_path, titles::unlinked_key(&game_path)),
+        )?;
+        if let Some(title_key) = title_key {
+            ensure_current_version(&tx, &title_key)?;
+            drop_orphan_title(&tx, &title_key)?;
+        }
+        tx.commit()
+    })
+}
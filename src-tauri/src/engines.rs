@@ -0,0 +1,91 @@
+use std::path::Path;
+
+// Содержимое папки игры: имена файлов и подпапок верхнего уровня.
+// Определение движка работает только с этим списком, а не с файловой системой,
+// поэтому его можно будет собрать и из другого источника, например из оглавления архива.
+pub struct DirListing {
+    // (имя в нижнем регистре, это папка)
+    entries: Vec<(String, bool)>,
+}
+
+impl DirListing {
+    pub fn from_dir(dir: &Path) -> Self {
+        let entries: Vec<(String, bool)> = std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|entry| {
+                        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+                        (entry.file_name().to_string_lossy().to_string(), is_dir)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self::from_entries(entries)
+    }
+
+    // Из уже прочитанного списка (имя, это папка), чтобы не читать папку второй раз
+    pub fn from_entries(entries: impl IntoIterator<Item = (String, bool)>) -> Self {
+        DirListing {
+            entries: entries
+                .into_iter()
+                .map(|(name, is_dir)| (name.to_lowercase(), is_dir))
+                .collect(),
+        }
+    }
+
+    fn has_dir(&self, name: &str) -> bool {
+        self.entries.iter().any(|(entry, is_dir)| *is_dir && entry == name)
+    }
+
+    fn has_file(&self, name: &str) -> bool {
+        self.entries.iter().any(|(entry, is_dir)| !*is_dir && entry == name)
+    }
+
+    fn any(&self, is_dir: bool, predicate: impl Fn(&str) -> bool) -> bool {
+        self.entries
+            .iter()
+            .any(|(entry, entry_is_dir)| *entry_is_dir == is_dir && predicate(entry))
+    }
+}
+
+// Определяет движок по структуре папки игры.
+// Порядок важен: у Ren'Py и RPG Maker тоже бывают index.html или .pck среди ресурсов.
+pub fn detect_engine(listing: &DirListing) -> Option<&'static str> {
+    if listing.has_dir("renpy") && listing.has_dir("game") {
+        return Some("renpy");
+    }
+    // MV кладет ресурсы в www/, MZ - прямо рядом с package.json
+    if listing.has_file("package.json")
+        && (listing.has_dir("www") || (listing.has_dir("js") && listing.has_dir("data")))
+    {
+        return Some("rpgmaker");
+    }
+    // До Unity 2017 рядом с игрой нет UnityPlayer.dll, зато папка данных
+    // всегда называется по исполняемому файлу: Game.exe и Game_Data/
+    if listing.any(true, |name| name.ends_with("_data"))
+        && (listing.any(false, |name| name.starts_with("unityplayer"))
+            || listing.any(false, |name| {
+                name.strip_suffix(".exe")
+                    .is_some_and(|stem| listing.has_dir(&format!("{}_data", stem)))
+            }))
+    {
+        return Some("unity");
+    }
+    if listing.any(false, |name| name.ends_with(".pck")) {
+        return Some("godot");
+    }
+    if listing.has_file("data.xp3") {
+        return Some("kirikiri");
+    }
+    if listing.has_file("index.html") {
+        return Some("html");
+    }
+    None
+}
+
+// Движок игры по пути к ее исполняемому файлу
+pub fn detect_engine_for_game(game_path: &str) -> Option<&'static str> {
+    let game_dir = Path::new(game_path).parent()?;
+    detect_engine(&DirListing::from_dir(game_dir))
+}
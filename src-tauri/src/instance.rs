@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::AppHandle;
+
+use crate::commands::launcher;
+use crate::database;
+
+// Файл в папке данных, по которому CLI находит запущенное окно лаунчера
+const INSTANCE_FILE: &str = "instance.json";
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+// Ответ приходит после pre_launch хуков, а они могут выполняться долго
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(300);
+// Запрос читается до проверки токена: чужой процесс не должен занять поток или память окна
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_REQUEST_BYTES: u64 = 64 * 1024;
+
+#[derive(Serialize, Deserialize)]
+struct InstanceInfo {
+    port: u16,
+    // Без токена запустить игру через порт мог бы любой локальный процесс
+    token: String,
+    pid: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LaunchRequest {
+    token: String,
+    // Номер, путь или имя игры - как их передали в CLI
+    game: String,
+    profile_id: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LaunchResponse {
+    pid: Option<u32>,
+    error: Option<String>,
+}
+
+// Чем закончилась попытка передать запуск окну
+pub enum Forwarded {
+    // Окно не запущено, CLI запускает игру сам
+    NoInstance,
+    Launched(u32),
+    Failed(String),
+}
+
+// 128 бит из криптографического генератора ОС
+fn random_token() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).map_err(|e| format!("Не удалось получить случайный токен: {}", e))?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+// Файл с токеном доступен только текущему пользователю
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    // mode действует только при создании: файл мог остаться от прошлой версии с обычными правами
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(contents.as_bytes())
+}
+
+// Запускается окном: принимает запросы на запуск игр от CLI на 127.0.0.1
+pub fn listen(app: AppHandle, app_data_dir: PathBuf) -> Result<(), String> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).map_err(|e| e.to_string())?;
+    let info = InstanceInfo {
+        port: listener.local_addr().map_err(|e| e.to_string())?.port(),
+        token: random_token()?,
+        pid: std::process::id(),
+    };
+    let json = serde_json::to_string(&info).map_err(|e| e.to_string())?;
+    write_private(&app_data_dir.join(INSTANCE_FILE), &json).map_err(|e| e.to_string())?;
+
+    let token = info.token;
+    std::thread::spawn(move || {
+        for stream in listener.incoming().filter_map(|s| s.ok()) {
+            let app = app.clone();
+            let app_data_dir = app_data_dir.clone();
+            let token = token.clone();
+            std::thread::spawn(move || handle_connection(stream, app, &app_data_dir, &token));
+        }
+    });
+    Ok(())
+}
+
+fn handle_connection(stream: TcpStream, app: AppHandle, app_data_dir: &Path, token: &str) {
+    let Ok(line) = read_request(&stream) else {
+        return;
+    };
+    let result = match serde_json::from_str::<LaunchRequest>(&line) {
+        Ok(request) if request.token == token => database::resolve_game(&request.game)
+            .and_then(|game| launcher::start_game(Some(app), app_data_dir, &game.path, request.profile_id))
+            .map(|(pid, _)| pid),
+        Ok(_) => Err("Неверный токен запроса".to_string()),
+        Err(e) => Err(format!("Некорректный запрос: {}", e)),
+    };
+    let response = match result {
+        Ok(pid) => LaunchResponse { pid: Some(pid), error: None },
+        Err(e) => LaunchResponse { pid: None, error: Some(e) },
+    };
+    if let Ok(json) = serde_json::to_string(&response) {
+        let _ = writeln!(&stream, "{}", json);
+    }
+}
+
+// Одна строка запроса не длиннее MAX_REQUEST_BYTES
+fn read_request(stream: &TcpStream) -> Result<String, String> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT)).map_err(|e| e.to_string())?;
+    let mut line = String::new();
+    BufReader::new(stream.take(MAX_REQUEST_BYTES + 1))
+        .read_line(&mut line)
+        .map_err(|e| e.to_string())?;
+    if line.len() as u64 > MAX_REQUEST_BYTES {
+        return Err("Слишком длинный запрос".to_string());
+    }
+    Ok(line)
+}
+
+fn process_alive(pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), true, ProcessRefreshKind::nothing());
+    system.process(pid).is_some()
+}
+
+// Вызывается из CLI: просит запущенное окно запустить игру,
+// чтобы не открывать вторую копию базы и не вести сессию в обход его реестра
+pub fn forward_launch(app_data_dir: &Path, game: &str, profile_id: Option<i64>) -> Forwarded {
+    let info = std::fs::read_to_string(app_data_dir.join(INSTANCE_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str::<InstanceInfo>(&json).ok());
+    // Файл остается, если окно было убито, а порт мог занять кто-то другой
+    let Some(info) = info.filter(|info| process_alive(info.pid)) else {
+        return Forwarded::NoInstance;
+    };
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, info.port));
+    let Ok(stream) = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) else {
+        return Forwarded::NoInstance;
+    };
+
+    let request = LaunchRequest {
+        token: info.token,
+        game: game.to_string(),
+        profile_id,
+    };
+    let exchange = || -> Result<LaunchResponse, String> {
+        stream.set_read_timeout(Some(RESPONSE_TIMEOUT)).map_err(|e| e.to_string())?;
+        let json = serde_json::to_string(&request).map_err(|e| e.to_string())?;
+        writeln!(&stream, "{}", json).map_err(|e| e.to_string())?;
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line).map_err(|e| e.to_string())?;
+        serde_json::from_str(&line).map_err(|e| e.to_string())
+    };
+    match exchange() {
+        Ok(LaunchResponse { pid: Some(pid), .. }) => Forwarded::Launched(pid),
+        Ok(LaunchResponse { error, .. }) => {
+            Forwarded::Failed(error.unwrap_or_else(|| "Неизвестная ошибка".to_string()))
+        }
+        Err(e) => Forwarded::Failed(format!("Не удалось связаться с окном лаунчера: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    // Соединение с сервером, которому клиент отправил data
+    fn server_side(data: Vec<u8>) -> TcpStream {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut client = TcpStream::connect(address).unwrap();
+            let _ = client.write_all(&data);
+            // Держим соединение открытым: сервер не должен ждать конца потока
+            std::thread::sleep(REQUEST_TIMEOUT * 2);
+        });
+        listener.accept().unwrap().0
+    }
+
+    #[test]
+    fn reads_one_request_line() {
+        let stream = server_side(b"{\"game\":\"1\"}\nrest".to_vec());
+        assert_eq!(read_request(&stream).unwrap(), "{\"game\":\"1\"}\n");
+    }
+
+    #[test]
+    fn rejects_overlong_request_without_newline() {
+        let stream = server_side(vec![b'a'; MAX_REQUEST_BYTES as usize * 2]);
+        assert!(read_request(&stream).is_err());
+    }
+}
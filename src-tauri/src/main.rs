@@ -9,6 +9,15 @@ use tauri_plugin_clipboard_manager::ClipboardExt;
 
 mod database;
 mod commands;
+mod process_tree;
+mod engines;
+mod renpy;
+mod pe_icon;
+mod cli;
+mod instance;
+mod scan_rules;
+mod archives;
+mod titles;
 
 #[tauri::command]
 async fn save_image_from_clipboard(app: AppHandle, game_path: String) -> Result<String, String> {
@@ -52,11 +61,24 @@ async fn save_image_from_clipboard(app: AppHandle, game_path: String) -> Result<
 
 // Главная функция, которая собирает и запускает приложение
 fn main() {
+    // Команды вида `rvnlauncher launch 3` выполняются без окна
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(exit_code) = cli::run(&args) {
+        std::process::exit(exit_code);
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_clipboard_manager::init())
         .setup(|app| {
             // Инициализируем БД при запуске
             database::init(&app.handle());
+            // Через этот канал CLI передает запуск игр уже открытому окну
+            let app_data_dir = app.path().app_data_dir()?;
+            if let Err(e) = instance::listen(app.handle().clone(), app_data_dir.clone()) {
+                eprintln!("Не удалось открыть канал для CLI: {}", e);
+            }
+            // Фоновое слежение за папками библиотеки (включается настройкой libraryWatcher)
+            commands::watcher::start(app.handle().clone(), app_data_dir);
             Ok(())
         })
         .plugin(tauri_plugin_os::init())
@@ -67,12 +89,30 @@ fn main() {
         // Регистрируем ВСЕ команды здесь
         .invoke_handler(tauri::generate_handler![
             commands::scanner::scan_for_games_recursively,
+            commands::scanner::start_scan,
+            commands::scanner::cancel_scan,
+            commands::install::install_from_archive,
+            commands::install::cancel_install,
+            commands::library::rescan_library,
+            commands::watcher::get_pending_games,
+            commands::watcher::resolve_pending_game,
             
             // Команды из модуля backups
             commands::backups::backup_saves,
             commands::backups::list_backups,
             commands::backups::delete_backup,
             commands::backups::restore_backup,
+
+            // Команды из модуля launcher
+            commands::launcher::launch_game,
+            commands::launcher::list_running_games,
+            commands::launcher::stop_game,
+            commands::logs::list_game_logs,
+            commands::logs::read_game_log,
+            commands::runners::detect_runners,
+            commands::icons::extract_exe_icons,
+            commands::shortcuts::create_desktop_shortcuts,
+            commands::shortcuts::remove_desktop_shortcuts,
             save_image_from_clipboard,
             database::db_add_game,
             database::db_get_games,
@@ -92,6 +132,35 @@ fn main() {
             database::db_get_setting,
             database::db_set_setting,
             database::db_update_game_completion,
+            database::db_get_play_sessions,
+            database::db_update_play_session,
+            database::db_delete_play_session,
+            database::db_get_interrupted_sessions,
+            database::db_resolve_interrupted_session,
+            database::db_get_launch_profiles,
+            database::db_save_launch_profile,
+            database::db_delete_launch_profile,
+            database::db_set_default_launch_profile,
+            database::db_get_runners,
+            database::db_save_runner,
+            database::db_delete_runner,
+            database::db_set_game_runner,
+            database::db_get_hooks,
+            database::db_save_hook,
+            database::db_delete_hook,
+            database::db_get_hook_runs,
+            database::db_set_game_auto_backup,
+            database::db_get_library_roots,
+            database::db_save_library_root,
+            database::db_delete_library_root,
+            database::db_get_scan_rules,
+            database::db_save_scan_rule,
+            database::db_delete_scan_rule,
+            database::db_reset_scan_rules,
+            database::db_get_game_versions,
+            database::db_set_current_version,
+            database::db_link_game_version,
+            database::db_unlink_game_version,
 
         ])
         .run(tauri::generate_context!())
@@ -0,0 +1,301 @@
+// Чтение иконки из ресурсов Windows-исполняемого файла (PE) без WinAPI,
+// чтобы обложки появлялись и для Windows-сборок, отсканированных на Linux.
+
+use std::io::{Read, Seek, SeekFrom};
+
+const RT_ICON: u32 = 3;
+const RT_GROUP_ICON: u32 = 14;
+const IMAGE_DIRECTORY_ENTRY_RESOURCE: usize = 2;
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+// Больше секция ресурсов не бывает даже у игр с видео в ресурсах: дальше файл, скорее всего, испорчен
+const MAX_RESOURCE_BYTES: u32 = 256 * 1024 * 1024;
+
+fn read_at<R: Read + Seek>(reader: &mut R, offset: u64, len: usize) -> Option<Vec<u8>> {
+    reader.seek(SeekFrom::Start(offset)).ok()?;
+    let mut buffer = vec![0; len];
+    reader.read_exact(&mut buffer).ok()?;
+    Some(buffer)
+}
+
+// Секция ресурсов (.rsrc). Из файла читаются только заголовки и она сама:
+// исполняемые файлы игр бывают размером в гигабайты.
+struct PeFile {
+    data: Vec<u8>,
+    // RVA начала секции: через него ссылки из дерева ресурсов переводятся в смещения в data
+    virtual_address: u32,
+    // Смещение корня дерева ресурсов в data
+    resource_base: usize,
+}
+
+impl PeFile {
+    fn parse<R: Read + Seek>(reader: &mut R) -> Option<Self> {
+        let dos = read_at(reader, 0, 64)?;
+        if dos.get(0..2)? != b"MZ" {
+            return None;
+        }
+        let pe_offset = read_u32(&dos, 0x3C)? as u64;
+        let coff = read_at(reader, pe_offset, 24)?;
+        if coff.get(0..4)? != b"PE\0\0" {
+            return None;
+        }
+        let section_count = read_u16(&coff, 6)? as usize;
+        let optional_size = read_u16(&coff, 20)? as usize;
+        // Опциональный заголовок и таблица секций идут сразу за COFF-заголовком
+        let headers = read_at(reader, pe_offset + 24, optional_size + section_count * 40)?;
+
+        // Таблица каталогов данных в PE32 и PE32+ начинается с разных смещений
+        let (rva_count_offset, directories_offset) = match read_u16(&headers, 0)? {
+            0x10b => (92, 96),
+            0x20b => (108, 112),
+            _ => return None,
+        };
+        let rva_count = read_u32(&headers, rva_count_offset)? as usize;
+        if rva_count <= IMAGE_DIRECTORY_ENTRY_RESOURCE {
+            return None;
+        }
+        let resource_rva =
+            read_u32(&headers, directories_offset + IMAGE_DIRECTORY_ENTRY_RESOURCE * 8)?;
+        if resource_rva == 0 {
+            return None;
+        }
+
+        // Секция, в которой лежит корень дерева ресурсов
+        let (virtual_address, raw_offset, raw_size) = (0..section_count).find_map(|i| {
+            let header = optional_size + i * 40;
+            let virtual_address = read_u32(&headers, header + 12)?;
+            let raw_size = read_u32(&headers, header + 16)?;
+            let raw_offset = read_u32(&headers, header + 20)?;
+            // Данные за пределами raw_size в файле не хранятся (заполняются нулями при загрузке)
+            let relative = resource_rva.checked_sub(virtual_address)?;
+            (relative < raw_size).then_some((virtual_address, raw_offset, raw_size))
+        })?;
+        if raw_size > MAX_RESOURCE_BYTES {
+            return None;
+        }
+        let data = read_at(reader, raw_offset as u64, raw_size as usize)?;
+        Some(PeFile {
+            data,
+            virtual_address,
+            resource_base: (resource_rva - virtual_address) as usize,
+        })
+    }
+
+    // Данные ресурсов компоновщики кладут в ту же секцию, что и дерево.
+    // Ссылки в другие секции не поддерживаем - такие файлы просто остаются без иконки.
+    fn rva_to_offset(&self, rva: u32) -> Option<usize> {
+        let relative = rva.checked_sub(self.virtual_address)? as usize;
+        (relative < self.data.len()).then_some(relative)
+    }
+
+    // Записи каталога ресурсов: (id, смещение, это подкаталог).
+    // Именованные записи идут первыми, их id не нужен - отдаем u32::MAX.
+    fn directory_entries(&self, directory: usize) -> Option<Vec<(u32, usize, bool)>> {
+        let offset = self.resource_base + directory;
+        let named = read_u16(&self.data, offset + 12)? as usize;
+        let ids = read_u16(&self.data, offset + 14)? as usize;
+        (0..named + ids)
+            .map(|i| {
+                let entry = offset + 16 + i * 8;
+                let name = read_u32(&self.data, entry)?;
+                let target = read_u32(&self.data, entry + 4)?;
+                let id = if name & 0x8000_0000 != 0 { u32::MAX } else { name };
+                Some((id, (target & 0x7FFF_FFFF) as usize, target & 0x8000_0000 != 0))
+            })
+            .collect()
+    }
+
+    // Первый ресурс внутри каталога (любой язык).
+    // Глубина ограничена: в испорченном файле каталоги могут ссылаться друг на друга по кругу.
+    fn first_data(&self, directory: usize) -> Option<&[u8]> {
+        let mut offset = directory;
+        let mut is_directory = true;
+        for _ in 0..3 {
+            if !is_directory {
+                break;
+            }
+            (_, offset, is_directory) = *self.directory_entries(offset)?.first()?;
+        }
+        if is_directory {
+            return None;
+        }
+        let entry = self.resource_base + offset;
+        let rva = read_u32(&self.data, entry)?;
+        let size = read_u32(&self.data, entry + 4)? as usize;
+        let start = self.rva_to_offset(rva)?;
+        self.data.get(start..start.checked_add(size)?)
+    }
+
+    // Каталог ресурсов данного типа (RT_ICON, RT_GROUP_ICON)
+    fn type_directory(&self, resource_type: u32) -> Option<usize> {
+        self.directory_entries(0)?
+            .into_iter()
+            .find(|(id, _, is_directory)| *id == resource_type && *is_directory)
+            .map(|(_, offset, _)| offset)
+    }
+
+    fn resource(&self, resource_type: u32, id: Option<u32>) -> Option<&[u8]> {
+        let type_directory = self.type_directory(resource_type)?;
+        let entries = self.directory_entries(type_directory)?;
+        let (_, offset, is_directory) = match id {
+            Some(id) => entries.into_iter().find(|(entry_id, _, _)| *entry_id == id)?,
+            None => entries.into_iter().next()?,
+        };
+        if is_directory {
+            self.first_data(offset)
+        } else {
+            None
+        }
+    }
+}
+
+// Самая большая картинка из первой группы иконок (обычно это иконка приложения)
+// в виде готового .ico файла с одной картинкой
+pub fn extract_largest_icon<R: Read + Seek>(reader: &mut R) -> Option<Vec<u8>> {
+    let pe = PeFile::parse(reader)?;
+    let group = pe.resource(RT_GROUP_ICON, None)?;
+    let count = read_u16(group, 4)? as usize;
+
+    // GRPICONDIRENTRY: 14 байт, ширина/высота 0 означают 256
+    let (entry, _) = (0..count)
+        .filter_map(|i| {
+            let entry = group.get(6 + i * 14..6 + (i + 1) * 14)?;
+            let width = if entry[0] == 0 { 256 } else { entry[0] as u32 };
+            let height = if entry[1] == 0 { 256 } else { entry[1] as u32 };
+            let bit_count = read_u16(entry, 6)? as u32;
+            Some((entry, (width * height, bit_count)))
+        })
+        .max_by_key(|(_, size)| *size)?;
+    let icon_id = read_u16(entry, 12)? as u32;
+    let image = pe.resource(RT_ICON, Some(icon_id))?;
+
+    // В ресурсах картинка лежит без заголовка .ico: собираем его, чтобы декодер
+    // одинаково понял и PNG, и BMP-вариант иконки
+    let mut ico = Vec::with_capacity(22 + image.len());
+    ico.extend_from_slice(&[0, 0, 1, 0, 1, 0]);
+    ico.extend_from_slice(&entry[0..8]);
+    ico.extend_from_slice(&(image.len() as u32).to_le_bytes());
+    ico.extend_from_slice(&22u32.to_le_bytes());
+    ico.extend_from_slice(image);
+    Some(ico)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const SECTION_RVA: u32 = 0x1000;
+    const SECTION_OFFSET: usize = 0x200;
+
+    fn directory(entries: &[(u32, u32)]) -> Vec<u8> {
+        let mut data = vec![0; 12];
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        for (id, target) in entries {
+            data.extend_from_slice(&id.to_le_bytes());
+            data.extend_from_slice(&target.to_le_bytes());
+        }
+        data
+    }
+
+    fn data_entry(rva: u32, size: usize) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&rva.to_le_bytes());
+        data.extend_from_slice(&(size as u32).to_le_bytes());
+        data.extend_from_slice(&[0; 8]);
+        data
+    }
+
+    fn group_entry(size: u8, bit_count: u16, bytes: usize, id: u16) -> Vec<u8> {
+        let mut data = vec![size, size, 0, 0];
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&bit_count.to_le_bytes());
+        data.extend_from_slice(&(bytes as u32).to_le_bytes());
+        data.extend_from_slice(&id.to_le_bytes());
+        data
+    }
+
+    // Минимальный PE32 с одной секцией .rsrc: группа из двух иконок, 16x16 и 256x256
+    fn fixture_pe(small: &[u8], large: &[u8]) -> Vec<u8> {
+        const DIR: u32 = 0x8000_0000;
+        let mut group = vec![0, 0, 1, 0, 2, 0];
+        group.extend(group_entry(16, 32, small.len(), 1));
+        group.extend(group_entry(0, 32, large.len(), 2));
+
+        // Смещения узлов дерева внутри секции
+        let (icon_types, group_types) = (32, 64);
+        let (small_lang, large_lang, group_lang) = (88, 112, 136);
+        let (small_entry, large_entry, group_data_entry) = (160, 176, 192);
+        let small_data = 208;
+        let large_data = small_data + small.len();
+        let group_data = large_data + large.len();
+
+        let mut rsrc = Vec::new();
+        rsrc.extend(directory(&[(RT_ICON, DIR | icon_types), (RT_GROUP_ICON, DIR | group_types)]));
+        rsrc.extend(directory(&[(1, DIR | small_lang), (2, DIR | large_lang)]));
+        rsrc.extend(directory(&[(1, DIR | group_lang)]));
+        rsrc.extend(directory(&[(0x409, small_entry)]));
+        rsrc.extend(directory(&[(0x409, large_entry)]));
+        rsrc.extend(directory(&[(0x409, group_data_entry)]));
+        rsrc.extend(data_entry(SECTION_RVA + small_data as u32, small.len()));
+        rsrc.extend(data_entry(SECTION_RVA + large_data as u32, large.len()));
+        rsrc.extend(data_entry(SECTION_RVA + group_data as u32, group.len()));
+        assert_eq!(rsrc.len(), small_data);
+        rsrc.extend_from_slice(small);
+        rsrc.extend_from_slice(large);
+        rsrc.extend(group);
+
+        let mut pe = vec![0; SECTION_OFFSET];
+        pe[0..2].copy_from_slice(b"MZ");
+        pe[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        pe[0x40..0x44].copy_from_slice(b"PE\0\0");
+        let coff = 0x44;
+        pe[coff + 2..coff + 4].copy_from_slice(&1u16.to_le_bytes());
+        pe[coff + 16..coff + 18].copy_from_slice(&224u16.to_le_bytes());
+        let optional = coff + 20;
+        pe[optional..optional + 2].copy_from_slice(&0x10bu16.to_le_bytes());
+        pe[optional + 92..optional + 96].copy_from_slice(&16u32.to_le_bytes());
+        let resource_directory = optional + 96 + IMAGE_DIRECTORY_ENTRY_RESOURCE * 8;
+        pe[resource_directory..resource_directory + 4].copy_from_slice(&SECTION_RVA.to_le_bytes());
+        let section = optional + 224;
+        pe[section..section + 5].copy_from_slice(b".rsrc");
+        pe[section + 8..section + 12].copy_from_slice(&(rsrc.len() as u32).to_le_bytes());
+        pe[section + 12..section + 16].copy_from_slice(&SECTION_RVA.to_le_bytes());
+        pe[section + 16..section + 20].copy_from_slice(&(rsrc.len() as u32).to_le_bytes());
+        pe[section + 20..section + 24].copy_from_slice(&(SECTION_OFFSET as u32).to_le_bytes());
+        pe.extend(rsrc);
+        pe
+    }
+
+    #[test]
+    fn extracts_largest_icon_as_ico() {
+        let pe = fixture_pe(b"small-icon", b"large-icon-data");
+        let ico = extract_largest_icon(&mut Cursor::new(pe)).expect("иконка должна найтись");
+
+        assert_eq!(&ico[0..6], &[0, 0, 1, 0, 1, 0]);
+        // Ширина 0 в заголовке .ico означает 256
+        assert_eq!(ico[6], 0);
+        assert_eq!(read_u32(&ico, 14), Some(15));
+        assert_eq!(read_u32(&ico, 18), Some(22));
+        assert_eq!(&ico[22..], b"large-icon-data");
+    }
+
+    #[test]
+    fn rejects_non_pe_and_truncated_files() {
+        assert!(extract_largest_icon(&mut Cursor::new(b"not an executable".to_vec())).is_none());
+
+        let mut pe = fixture_pe(b"small", b"large");
+        pe.truncate(SECTION_OFFSET + 40);
+        assert!(extract_largest_icon(&mut Cursor::new(pe)).is_none());
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, System};
+
+// Запас по времени старта: часы процесса и наши часы могут немного расходиться
+const START_TIME_SLACK_SECS: u64 = 5;
+
+// Работает ли процесс pid, запущенный не позже since (секунды UNIX)
+pub fn is_alive_since(pid: u32, since: u64) -> bool {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), true, ProcessRefreshKind::nothing());
+    system
+        .process(pid)
+        .is_some_and(|process| process.start_time() <= since.saturating_add(START_TIME_SLACK_SECS))
+}
+
+// Дерево процессов, порожденных запуском игры.
+// Ren'Py и Unity часто перезапускают себя в дочернем процессе, а исходный процесс
+// сразу завершается, поэтому ждать только его недостаточно.
+pub struct ProcessTree {
+    system: System,
+    // Нижняя граница времени старта для процессов сессии (секунды UNIX)
+    started_after: u64,
+    // Все процессы, которые когда-либо входили в дерево, с их временем старта.
+    // Умершие процессы не удаляются: на Windows у потомков остается PID умершего родителя.
+    lineage: HashMap<Pid, u64>,
+}
+
+impl ProcessTree {
+    pub fn new(root_pid: u32) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let started_after = now.saturating_sub(START_TIME_SLACK_SECS);
+
+        let mut tree = ProcessTree {
+            system: System::new(),
+            started_after,
+            lineage: HashMap::new(),
+        };
+        tree.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing(),
+        );
+        let root = Pid::from_u32(root_pid);
+        let root_start = tree
+            .system
+            .process(root)
+            .map(|p| p.start_time())
+            .unwrap_or(now);
+        tree.lineage.insert(root, root_start);
+        tree
+    }
+
+    // Обновляет список процессов системы, добавляет новых потомков
+    // и возвращает PID-ы процессов дерева, которые еще работают
+    pub fn refresh(&mut self) -> Vec<u32> {
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing(),
+        );
+        let processes = self.system.processes();
+
+        // Потомок может сам успеть породить процесс, поэтому повторяем до стабилизации
+        loop {
+            let mut adopted = Vec::new();
+            for (pid, process) in processes {
+                if self.lineage.contains_key(pid) || process.start_time() < self.started_after {
+                    continue;
+                }
+                let Some(parent) = process.parent() else { continue };
+                let Some(&parent_start) = self.lineage.get(&parent) else { continue };
+
+                // Если PID родителя уже занят чужим процессом, это не наш потомок
+                let parent_reused = processes
+                    .get(&parent)
+                    .is_some_and(|p| p.start_time() != parent_start);
+                if !parent_reused && process.start_time() >= parent_start {
+                    adopted.push((*pid, process.start_time()));
+                }
+            }
+            if adopted.is_empty() {
+                break;
+            }
+            self.lineage.extend(adopted);
+        }
+
+        self.lineage
+            .iter()
+            .filter(|(pid, start)| {
+                processes.get(pid).is_some_and(|p| {
+                    p.start_time() == **start && p.status() != ProcessStatus::Zombie
+                })
+            })
+            .map(|(pid, _)| pid.as_u32())
+            .collect()
+    }
+}
+
+// Просит процессы завершиться (SIGTERM на Unix, закрытие окон на Windows)
+// или, если force = true, убивает их принудительно
+pub fn terminate(pids: &[u32], force: bool) {
+    let pids: Vec<Pid> = pids.iter().map(|pid| Pid::from_u32(*pid)).collect();
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&pids),
+        true,
+        ProcessRefreshKind::nothing(),
+    );
+    for pid in &pids {
+        if let Some(process) = system.process(*pid) {
+            if force {
+                process.kill();
+            } else {
+                request_close(process);
+            }
+        }
+    }
+}
+
+// Убивает группу процессов, созданную через process_group(0), вместе с фоновыми
+// процессами, которые уже отвязались от родителя
+#[cfg(unix)]
+pub fn kill_process_group(pgid: u32) {
+    let _ = std::process::Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", pgid)])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+}
+
+#[cfg(not(windows))]
+fn request_close(process: &Process) {
+    process.kill_with(sysinfo::Signal::Term);
+}
+
+// На Windows нет SIGTERM: taskkill без /F посылает окнам процесса WM_CLOSE
+#[cfg(windows)]
+fn request_close(process: &Process) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+    let _ = std::process::Command::new("taskkill")
+        .args(["/PID", &process.pid().as_u32().to_string()])
+        .creation_flags(CREATE_NO_WINDOW)
+        .status();
+}
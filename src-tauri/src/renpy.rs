@@ -0,0 +1,114 @@
+use std::fs;
+use std::path::Path;
+
+// Метаданные Ren'Py-игры из game/options.rpy и из SDK в папке renpy/
+#[derive(Debug, Default, Clone)]
+pub struct RenpyMetadata {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub save_directory: Option<String>,
+    pub build_name: Option<String>,
+    // Версия SDK, с которым собрана игра, например "8.1.3.23091805"
+    pub sdk_version: Option<String>,
+}
+
+// game_dir - папка игры, в которой лежат game/ и renpy/
+pub fn read_metadata(game_dir: &Path) -> RenpyMetadata {
+    let mut metadata = RenpyMetadata::default();
+    if let Ok(bytes) = fs::read(game_dir.join("game").join("options.rpy")) {
+        let text = String::from_utf8_lossy(&bytes);
+        for line in text.lines() {
+            let Some((key, value)) = parse_assignment(line) else {
+                continue;
+            };
+            let target = match key {
+                "config.name" => &mut metadata.name,
+                "config.version" => &mut metadata.version,
+                "config.save_directory" => &mut metadata.save_directory,
+                "build.name" => &mut metadata.build_name,
+                _ => continue,
+            };
+            // Первое значение - основное, дальше бывают переопределения под платформы
+            if target.is_none() {
+                *target = Some(value);
+            }
+        }
+    }
+    metadata.sdk_version = read_sdk_version(&game_dir.join("renpy"));
+    metadata
+}
+
+// Разбирает `define config.name = _("Title")` или `config.version = "1.0"`
+// (старые игры задают настройки внутри init python).
+// Возвращает None, если значение не строковый литерал: выражения мы не вычисляем.
+fn parse_assignment(line: &str) -> Option<(&str, String)> {
+    let line = line.trim();
+    if line.starts_with('#') {
+        return None;
+    }
+    let line = line.strip_prefix("define ").unwrap_or(line).trim_start();
+    let (key, value) = line.split_once('=')?;
+    let key = key.trim();
+    if key.contains(char::is_whitespace) {
+        return None;
+    }
+    let value = value.trim();
+    // _("...") - строка для перевода
+    let value = value
+        .strip_prefix("_(")
+        .and_then(|v| v.strip_suffix(')'))
+        .unwrap_or(value);
+    let literal = parse_string_literal(value)?;
+    let literal = literal.trim();
+    (!literal.is_empty()).then(|| (key, literal.to_string()))
+}
+
+// Строка в одинарных или двойных кавычках, с префиксом u"" из Python 2
+fn parse_string_literal(value: &str) -> Option<String> {
+    let value = value.strip_prefix('u').unwrap_or(value);
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let mut result = String::new();
+    let mut chars = value[1..].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.push(chars.next()?),
+            c if c == quote => return Some(result),
+            c => result.push(c),
+        }
+    }
+    None
+}
+
+// Значение `name = ...` из Python-модуля SDK, без кавычек
+fn python_value(text: &str, name: &str) -> Option<String> {
+    text.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == name).then(|| value.trim().trim_matches(['\'', '"']).to_string())
+    })
+}
+
+// Ren'Py 7.4+ пишет полную версию в renpy/vc_version.py (`version = '8.1.3.23091805'`).
+// В более старых там только номер сборки, а сама версия - в version_tuple из renpy/__init__.py.
+fn read_sdk_version(renpy_dir: &Path) -> Option<String> {
+    let vc_version = fs::read_to_string(renpy_dir.join("vc_version.py")).unwrap_or_default();
+    if let Some(version) = python_value(&vc_version, "version").filter(|v| !v.is_empty()) {
+        return Some(version);
+    }
+
+    let init = fs::read_to_string(renpy_dir.join("__init__.py")).ok()?;
+    let tuple = python_value(&init, "version_tuple")?;
+    // (7, 3, 5, vc_version) или VersionTuple(8, 0, 0, vc_version)
+    let inner = tuple.split_once('(')?.1.split_once(')')?.0;
+    let build = python_value(&vc_version, "vc_version");
+    let parts: Vec<String> = inner
+        .split(',')
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .filter_map(|part| match part {
+            "vc_version" => build.clone(),
+            part if part.chars().all(|c| c.is_ascii_digit()) => Some(part.to_string()),
+            _ => None,
+        })
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("."))
+}
@@ -0,0 +1,268 @@
+use regex::{Regex, RegexBuilder};
+use std::path::Path;
+
+use crate::database::{self, ScanRule};
+
+// Встроенные правила исключения: (к чему применяется, вид шаблона, шаблон).
+// Записываются в таблицу scan_rules при создании базы, дальше их можно менять и отключать.
+pub const BUILTIN_RULES: &[(&str, &str, &str)] = &[
+    ("dir", "exact", "renpy"),
+    ("dir", "exact", "lib"),
+    ("dir", "exact", "python-packages"),
+    ("dir", "exact", "common"),
+    ("dir", "exact", "saves"),
+    ("dir", "exact", "update"),
+    ("dir", "exact", "game"),
+    ("dir", "exact", "python27"),
+    // Только сам интерпретатор: под python*.exe попадали и игры ("PythonQuest.exe")
+    ("file", "exact", "python.exe"),
+    ("file", "exact", "pythonw.exe"),
+    ("file", "glob", "zsync*.exe"),
+    ("file", "glob", "librenpy*.exe"),
+    ("file", "exact", "renpy.exe"),
+    ("file", "glob", "UnityCrashHandler*.exe"),
+    ("file", "exact", "notification_helper.exe"),
+];
+
+// Имя файла, в котором можно исключить что-то из сканирования прямо в папке с играми
+pub const IGNORE_FILE_NAME: &str = ".rvnignore";
+
+enum Matcher {
+    Exact(String),
+    // Шаблон в нижнем регистре: * - любая последовательность символов, ? - один символ
+    Glob(Vec<char>),
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn new(kind: &str, pattern: &str) -> Result<Self, String> {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            return Err("Пустой шаблон".to_string());
+        }
+        // Сканер сравнивает шаблон с именем файла или папки, путь в него не входит
+        if kind != "regex" && pattern.contains(['/', '\\']) {
+            return Err(format!(
+                "Шаблон {:?} содержит путь, а сравнивается только с именем файла или папки",
+                pattern
+            ));
+        }
+        match kind {
+            "exact" => Ok(Matcher::Exact(pattern.to_lowercase())),
+            "glob" => Ok(Matcher::Glob(pattern.to_lowercase().chars().collect())),
+            "regex" => RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map(Matcher::Regex)
+                .map_err(|e| format!("Неверное регулярное выражение {:?}: {}", pattern, e)),
+            _ => Err(format!("Неизвестный вид шаблона: {}", kind)),
+        }
+    }
+
+    fn matches(&self, name: &str, lowercase: &str) -> bool {
+        match self {
+            Matcher::Exact(exact) => exact == lowercase,
+            Matcher::Glob(glob) => glob_matches(glob, &lowercase.chars().collect::<Vec<_>>()),
+            Matcher::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+// Сопоставление с возвратом к последней звездочке: без рекурсии и экспоненциальных случаев
+fn glob_matches(glob: &[char], name: &[char]) -> bool {
+    let (mut g, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match glob.get(g) {
+            Some('*') => {
+                star = Some((g, n));
+                g += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                g += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_g, star_n)) => {
+                    g = star_g + 1;
+                    n = star_n + 1;
+                    star = Some((star_g, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    glob[g..].iter().all(|c| *c == '*')
+}
+
+// Проверяет шаблон перед сохранением правила
+pub fn validate(kind: &str, pattern: &str) -> Result<(), String> {
+    Matcher::new(kind, pattern).map(|_| ())
+}
+
+// Набор правил, по которым сканер пропускает папки и исполняемые файлы
+#[derive(Default)]
+pub struct ScanRules {
+    dirs: Vec<Matcher>,
+    files: Vec<Matcher>,
+}
+
+impl ScanRules {
+    fn push(&mut self, target: &str, kind: &str, pattern: &str) -> Result<(), String> {
+        let matcher = Matcher::new(kind, pattern)?;
+        match target {
+            "dir" => self.dirs.push(matcher),
+            "file" => self.files.push(matcher),
+            _ => return Err(format!("Неизвестная цель правила: {}", target)),
+        }
+        Ok(())
+    }
+
+    pub fn builtin() -> Self {
+        let mut rules = ScanRules::default();
+        for (target, kind, pattern) in BUILTIN_RULES {
+            // Встроенные шаблоны заведомо корректны
+            let _ = rules.push(target, kind, pattern);
+        }
+        rules
+    }
+
+    // Включенные правила из базы. Битое правило пропускаем, а не останавливаем сканирование.
+    pub fn from_rules(saved: &[ScanRule]) -> Self {
+        let mut rules = ScanRules::default();
+        for rule in saved.iter().filter(|rule| rule.enabled) {
+            if let Err(e) = rules.push(&rule.target, &rule.kind, &rule.pattern) {
+                eprintln!("Правило сканирования {:?} пропущено: {}", rule.pattern, e);
+            }
+        }
+        rules
+    }
+
+    // Правила из базы; если база недоступна - встроенные
+    pub fn load() -> Self {
+        match database::db_get_scan_rules() {
+            Ok(saved) => Self::from_rules(&saved),
+            Err(e) => {
+                eprintln!("Не удалось загрузить правила сканирования: {}", e);
+                Self::builtin()
+            }
+        }
+    }
+
+    // Дополнительные glob-шаблоны конкретной папки библиотеки (library_roots)
+    pub fn with_globs(mut self, dirs: &[String], files: &[String]) -> Self {
+        for pattern in dirs.iter().filter(|p| !p.trim().is_empty()) {
+            let _ = self.push("dir", "glob", pattern);
+        }
+        for pattern in files.iter().filter(|p| !p.trim().is_empty()) {
+            let _ = self.push("file", "glob", pattern);
+        }
+        self
+    }
+
+    pub fn read_ignore_file(dir: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(dir.join(IGNORE_FILE_NAME)).ok()?;
+        Some(Self::parse_ignore_file(&text))
+    }
+
+    // .rvnignore: по одному glob-шаблону на строку, # - комментарий.
+    // Шаблон с / на конце относится только к папкам, остальные - и к папкам, и к файлам.
+    // Шаблоны сравниваются с именами на любой глубине, строки с путем ("art/raw") пропускаются.
+    // Пустой файл исключает всю папку.
+    fn parse_ignore_file(text: &str) -> Self {
+        let mut rules = ScanRules::default();
+        let patterns: Vec<&str> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+        if patterns.is_empty() {
+            let _ = rules.push("dir", "glob", "*");
+            let _ = rules.push("file", "glob", "*");
+        }
+        for pattern in patterns {
+            let pushed = match pattern.strip_suffix('/') {
+                Some(dir_pattern) => rules.push("dir", "glob", dir_pattern),
+                None => rules
+                    .push("dir", "glob", pattern)
+                    .and_then(|()| rules.push("file", "glob", pattern)),
+            };
+            if let Err(e) = pushed {
+                eprintln!("{}: строка пропущена: {}", IGNORE_FILE_NAME, e);
+            }
+        }
+        rules
+    }
+
+    pub fn excludes(&self, name: &str, is_dir: bool) -> bool {
+        let lowercase = name.to_lowercase();
+        let matchers = if is_dir { &self.dirs } else { &self.files };
+        matchers.iter().any(|matcher| matcher.matches(name, &lowercase))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob(pattern: &str, name: &str) -> bool {
+        glob_matches(&pattern.chars().collect::<Vec<_>>(), &name.chars().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn glob_matcher() {
+        assert!(glob("*.exe", "game.exe"));
+        assert!(glob("unitycrashhandler*.exe", "unitycrashhandler64.exe"));
+        assert!(glob("python?.exe", "python3.exe"));
+        assert!(!glob("python?.exe", "python.exe"));
+        assert!(glob("a*b*c", "aXXbYYbc"));
+        assert!(glob("*", ""));
+        assert!(glob("**", "anything"));
+        assert!(!glob("*.exe", "game.exe.bak"));
+        assert!(!glob("game", "game2"));
+        assert!(!glob("", "game"));
+    }
+
+    #[test]
+    fn builtin_rules_skip_only_the_python_interpreter() {
+        let rules = ScanRules::builtin();
+        assert!(rules.excludes("python.exe", false));
+        assert!(rules.excludes("PythonW.exe", false));
+        assert!(!rules.excludes("PythonQuest.exe", false));
+        assert!(rules.excludes("UnityCrashHandler64.exe", false));
+        assert!(rules.excludes("renpy", true));
+        assert!(!rules.excludes("renpy", false));
+    }
+
+    #[test]
+    fn patterns_with_a_path_are_rejected() {
+        assert!(validate("glob", "art/raw").is_err());
+        assert!(validate("exact", "bin\\game.exe").is_err());
+        assert!(validate("regex", "^a/b$").is_ok());
+        assert!(validate("glob", "  ").is_err());
+        assert!(validate("regex", "(").is_err());
+    }
+
+    #[test]
+    fn ignore_file_parsing() {
+        let rules = ScanRules::parse_ignore_file(
+            "# исходники арта\n\n  extras/  \n*.bak.exe\nart/raw\n/root-only\n",
+        );
+        assert!(rules.excludes("Extras", true));
+        assert!(!rules.excludes("extras", false));
+        assert!(rules.excludes("old.bak.exe", false));
+        assert!(rules.excludes("old.bak.exe", true));
+        // Строки с путем не действуют, а не исключают лишнее
+        assert!(!rules.excludes("raw", true));
+        assert!(!rules.excludes("art", true));
+        assert!(!rules.excludes("root-only", true));
+        assert!(!rules.excludes("game.exe", false));
+    }
+
+    #[test]
+    fn empty_ignore_file_excludes_everything() {
+        let rules = ScanRules::parse_ignore_file("# только комментарий\n\n");
+        assert!(rules.excludes("anything", true));
+        assert!(rules.excludes("game.exe", false));
+    }
+}
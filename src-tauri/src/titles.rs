@@ -0,0 +1,225 @@
+// Объединение установленных версий одной игры ("Game-0.5-pc" и "Game-0.6-pc") под общим названием.
+use std::cmp::Ordering;
+use std::path::Path;
+
+// Название без версии и платформы: "Game-0.5-pc" -> "Game". Номер сразу после имени
+// остается частью названия: "Game 2" и "Episode 1" - разные игры, а не версии одной.
+pub fn guess_title(name: &str) -> String {
+    let words: Vec<String> = name
+        .split(['-', '_', ' '])
+        .flat_map(split_dots)
+        .filter(|word| !word.is_empty())
+        .take_while(|word| {
+            let lowercase = word.to_lowercase();
+            !is_version_word(&lowercase)
+                && !matches!(lowercase.as_str(), "pc" | "win" | "windows" | "linux" | "mac" | "market")
+        })
+        .collect();
+    if words.is_empty() {
+        name.to_string()
+    } else {
+        words.join(" ")
+    }
+}
+
+// Точка разделяет слова ("Game.v0.5"), но не числа внутри версии ("0.5", "v1.0.3")
+fn split_dots(word: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for part in word.split('.') {
+        match words.last_mut() {
+            Some(last)
+                if last.ends_with(|c: char| c.is_ascii_digit())
+                    && part.starts_with(|c: char| c.is_ascii_digit()) =>
+            {
+                last.push('.');
+                last.push_str(part);
+            }
+            _ => words.push(part.to_string()),
+        }
+    }
+    words
+}
+
+// "0.5", "1.0.3b", "v1.2", "v05"; просто число ("2") версией не считается
+fn is_version_word(lowercase: &str) -> bool {
+    let (has_prefix, rest) = match lowercase.strip_prefix('v') {
+        Some(rest) => (true, rest),
+        None => (false, lowercase),
+    };
+    rest.starts_with(|c: char| c.is_ascii_digit())
+        && rest.chars().all(|c| c.is_ascii_alphanumeric() || c == '.')
+        && (has_prefix || rest.contains('.'))
+}
+
+// Папки сборок под платформу внутри папки игры ("Game/Windows/Game.exe"): по ним название не угадать
+const PLATFORM_FOLDERS: &[&str] = &[
+    "windows", "win", "win32", "win64", "pc", "x64", "x86", "x8664", "amd64", "linux", "linux64", "mac", "macos",
+    "osx", "build", "builds", "bin", "binaries", "release", "dist",
+];
+
+fn normalize(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// Название по ближайшей к .exe папке, которая не платформа, не сборка и не номер версии
+fn folder_title(path: &str) -> Option<String> {
+    Path::new(path)
+        .ancestors()
+        .skip(1)
+        .take(3)
+        .filter_map(|dir| dir.file_name())
+        .map(|dir| guess_title(&dir.to_string_lossy()))
+        .find(|title| {
+            let key = normalize(title);
+            key.chars().any(char::is_alphabetic)
+                && !PLATFORM_FOLDERS.contains(&key.as_str())
+                // "v0.5"
+                && !key.strip_prefix('v').is_some_and(|rest| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit()))
+        })
+}
+
+// Ключ, по которому версии попадают в одно название: только буквы и цифры в нижнем регистре.
+// Имя из options.rpy или заданное пользователем надежнее, чем имя .exe ("Game.exe" у RPG Maker),
+// поэтому без него название угадываем по папке игры.
+pub fn title_key(path: &str, name: &str, name_source: Option<&str>) -> String {
+    let title = match (name_source, folder_title(path)) {
+        (Some("options.rpy" | "user"), _) | (_, None) => name.to_string(),
+        (_, Some(folder_title)) => folder_title,
+    };
+    let key = normalize(&title);
+    if key.is_empty() {
+        // Ни с чем не объединяется
+        unlinked_key(path)
+    } else {
+        key
+    }
+}
+
+// Ключ версии, отвязанной от названия: уникален, потому что содержит путь
+pub fn unlinked_key(path: &str) -> String {
+    format!("path:{}", path)
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum VersionPart {
+    // Буквенная часть младше числовой на том же месте: "1.0b" < "1.0.1"
+    Text(String),
+    Number(u64),
+}
+
+fn version_parts(version: &str) -> Vec<VersionPart> {
+    let mut parts = Vec::new();
+    let mut chars = version.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_ascii_digit() {
+            let mut digits = String::new();
+            while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                digits.push(c);
+            }
+            parts.push(VersionPart::Number(digits.parse().unwrap_or(u64::MAX)));
+        } else if c.is_alphabetic() {
+            let mut text = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_alphabetic()) {
+                text.extend(c.to_lowercase());
+            }
+            parts.push(VersionPart::Text(text));
+        } else {
+            chars.next();
+        }
+    }
+    parts
+}
+
+// Сравнение версий по числам: "0.10" новее "0.9". Суффикс из букв означает предварительную
+// сборку: "1.0b" и "1.0rc1" старше "1.0". Версия без номера считается самой старой.
+pub fn compare_versions(a: Option<&str>, b: Option<&str>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => compare_parts(&version_parts(a), &version_parts(b)),
+        (a, b) => a.is_some().cmp(&b.is_some()),
+    }
+}
+
+fn compare_parts(a: &[VersionPart], b: &[VersionPart]) -> Ordering {
+    // "v1.0" и "1.0" - одна версия
+    let strip_v = |parts: &[VersionPart]| match parts.first() {
+        Some(VersionPart::Text(text)) if text == "v" => 1,
+        _ => 0,
+    };
+    let (a, b) = (&a[strip_v(a)..], &b[strip_v(b)..]);
+    for (part_a, part_b) in a.iter().zip(b) {
+        match part_a.cmp(part_b) {
+            Ordering::Equal => {}
+            other => return other,
+        }
+    }
+    // Общее начало совпало: лишнее число делает версию новее ("1.0.1" > "1.0"), лишний текст - старше
+    match (a.get(b.len()), b.get(a.len())) {
+        (Some(VersionPart::Number(_)), _) | (_, Some(VersionPart::Text(_))) => Ordering::Greater,
+        (Some(VersionPart::Text(_)), _) | (_, Some(VersionPart::Number(_))) => Ordering::Less,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guess_title_drops_version_and_platform() {
+        assert_eq!(guess_title("Game-0.5-pc"), "Game");
+        assert_eq!(guess_title("My_Great Game-v1.2.3-win"), "My Great Game");
+        assert_eq!(guess_title("Game-Market"), "Game");
+        assert_eq!(guess_title("0.5"), "0.5");
+        assert_eq!(guess_title("Game_v05"), "Game");
+        assert_eq!(guess_title("Game.v1.0.3b"), "Game");
+    }
+
+    #[test]
+    fn guess_title_keeps_sequel_and_episode_numbers() {
+        assert_eq!(guess_title("Game 2"), "Game 2");
+        assert_eq!(guess_title("Game 2-1.0-pc"), "Game 2");
+        assert_eq!(guess_title("Game 3: Reborn"), "Game 3: Reborn");
+        assert_eq!(guess_title("Episode_1-v0.3"), "Episode 1");
+        assert_ne!(
+            title_key("/games/Chapter 1-0.5/Game.exe", "Game", None),
+            title_key("/games/Chapter 2-0.5/Game.exe", "Game", None)
+        );
+    }
+
+    #[test]
+    fn title_key_skips_platform_and_version_folders() {
+        let game = |path| title_key(path, "Game", None);
+        assert_eq!(game("/games/Game-0.5-pc/Game.exe"), "game");
+        assert_eq!(game("/games/Other Game/Windows/Game.exe"), "othergame");
+        assert_eq!(game("/games/Other Game/v0.6/PC/Game.exe"), "othergame");
+        assert_ne!(game("/games/Alpha/Windows/Game.exe"), game("/games/Beta/Windows/Game.exe"));
+        assert_ne!(game("/games/Alpha/PC/Game.exe"), game("/games/Beta/PC/Game.exe"));
+    }
+
+    #[test]
+    fn title_key_prefers_reliable_names() {
+        assert_eq!(title_key("/games/Folder/Game.exe", "Real Name", Some("options.rpy")), "realname");
+        assert_eq!(title_key("/games/Folder/Game.exe", "Real Name", Some("user")), "realname");
+        assert_eq!(title_key("/Windows/Game.exe", "Game", None), "game");
+        assert_eq!(title_key("/games/x/!!!.exe", "!!!", Some("user")), unlinked_key("/games/x/!!!.exe"));
+    }
+
+    #[test]
+    fn compare_versions_orders_numbers_and_prereleases() {
+        let cmp = |a, b| compare_versions(Some(a), Some(b));
+        assert_eq!(cmp("0.10", "0.9"), Ordering::Greater);
+        assert_eq!(cmp("1.0.1", "1.0"), Ordering::Greater);
+        assert_eq!(cmp("1.0b", "1.0"), Ordering::Less);
+        assert_eq!(cmp("1.0rc1", "1.0"), Ordering::Less);
+        assert_eq!(cmp("1.0b", "1.0.1"), Ordering::Less);
+        assert_eq!(cmp("1.0a", "1.0b"), Ordering::Less);
+        assert_eq!(cmp("v1.0", "1.0"), Ordering::Equal);
+        assert_eq!(cmp("1.0", "1.0b"), Ordering::Greater);
+        assert_eq!(compare_versions(None, Some("0.1")), Ordering::Less);
+        assert_eq!(compare_versions(None, None), Ordering::Equal);
+    }
+}
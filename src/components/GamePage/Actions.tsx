@@ -32,7 +32,7 @@ const ActionButton = ({ children, onClick, className = '' }: { children: React.R
 
 
 export function GamePageActions({ game, gameFolderIds, setGameFolderIds, handlers }: GamePageActionsProps) {
-    const { addSession, removeSession, isGameRunning } = useGameSession();
+    const { isGameRunning, stopGame } = useGameSession();
     const isRunning = isGameRunning(game.path);
 
     return (
@@ -40,11 +40,10 @@ export function GamePageActions({ game, gameFolderIds, setGameFolderIds, handler
             <motion.button
                 whileHover={{ scale: 1.05 }}
                 whileTap={{ scale: 0.95 }}
-                onClick={() => callLaunchGameCommand(game.path, addSession, removeSession)}
-                disabled={isRunning}
-                className="h-10 px-6 bg-[#5417cf] text-text-primary text-sm font-bold rounded-lg transition-colors disabled:bg-gray-500 disabled:cursor-not-allowed"
+                onClick={() => isRunning ? stopGame(game.path) : callLaunchGameCommand(game.path)}
+                className={`h-10 px-6 text-text-primary text-sm font-bold rounded-lg transition-colors ${isRunning ? 'bg-accent-danger/80' : 'bg-[#5417cf]'}`}
             >
-                {isRunning ? 'Запущена' : 'Играть'}
+                {isRunning ? 'Остановить' : 'Играть'}
             </motion.button>
 
             <GameFolderManager 
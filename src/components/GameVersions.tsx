@@ -0,0 +1,127 @@
+import { useCallback, useEffect, useState } from 'react';
+import { useNavigate } from 'react-router-dom';
+import toast from 'react-hot-toast';
+import { GameEntry, getAllGamesFromDb, getGameVersions, linkGameVersion, setCurrentVersion, unlinkGameVersion } from '../utils/db';
+import { callLaunchGameCommand } from '../utils/start-game';
+import { formatPlaytime } from '../utils/formatters';
+import { useGameSession } from '../context/GameSessionContext';
+
+interface GameVersionsProps {
+  game: GameEntry;
+}
+
+// Установленные версии названия: запуск старых сборок, выбор текущей, ручное объединение
+export function GameVersions({ game }: GameVersionsProps) {
+  const navigate = useNavigate();
+  const { isGameRunning } = useGameSession();
+  const [versions, setVersions] = useState<GameEntry[]>([]);
+  const [otherTitles, setOtherTitles] = useState<GameEntry[]>([]);
+  const [linkTarget, setLinkTarget] = useState('');
+
+  const loadVersions = useCallback(async () => {
+    try {
+      const [titleVersions, titles] = await Promise.all([getGameVersions(game.path), getAllGamesFromDb()]);
+      setVersions(titleVersions);
+      setOtherTitles(titles.filter(title => !titleVersions.some(version => version.path === title.path)));
+    } catch (e) {
+      console.error('Не удалось загрузить версии:', e);
+    }
+  }, [game.path]);
+
+  useEffect(() => {
+    loadVersions();
+  }, [loadVersions]);
+
+  // Страница игры открыта по пути текущей версии: после смены текущей переходим на новый путь
+  const openTitle = (path: string) => navigate(`/game/${encodeURIComponent(path)}`, { replace: true });
+
+  const handleSetCurrent = async (version: GameEntry) => {
+    await setCurrentVersion(version.path);
+    openTitle(version.path);
+  };
+
+  const handleUnlink = async (version: GameEntry) => {
+    await unlinkGameVersion(version.path);
+    toast.success(`Версия ${version.version ?? ''} отделена в отдельную игру`);
+    if (version.path === game.path) {
+      const rest = versions.filter(v => v.path !== version.path);
+      if (rest.length > 0) {
+        openTitle(rest[0].path);
+        return;
+      }
+    }
+    loadVersions();
+  };
+
+  const handleLink = async () => {
+    if (!linkTarget) return;
+    await linkGameVersion(game.path, linkTarget);
+    setLinkTarget('');
+    openTitle(linkTarget);
+  };
+
+  return (
+    <div className="mt-8 space-y-6">
+      <div>
+        <h2 className="text-2xl font-bold mb-2 text-text-primary">Установленные версии</h2>
+        <ul className="space-y-2">
+          {versions.map(version => (
+            <li key={version.path} className="flex items-center gap-4 bg-secondary px-4 py-3 rounded-lg">
+              <span className="font-semibold">{version.version || 'без версии'}</span>
+              {version.is_current_version && <span className="text-xs px-2 py-0.5 rounded-full bg-[#5417cf]">текущая</span>}
+              <span className="flex-1 text-sm text-text-secondary truncate" title={version.path}>{version.path}</span>
+              <span className="text-sm text-text-secondary">{formatPlaytime(version.play_time_seconds)}</span>
+              <button
+                onClick={() => callLaunchGameCommand(version.path)}
+                disabled={isGameRunning(version.path)}
+                className="px-3 py-1 rounded-full bg-primary hover:bg-tertiary text-sm disabled:opacity-50"
+              >
+                Играть
+              </button>
+              {!version.is_current_version && (
+                <button onClick={() => handleSetCurrent(version)} className="px-3 py-1 rounded-full bg-primary hover:bg-tertiary text-sm">
+                  Сделать текущей
+                </button>
+              )}
+              {versions.length > 1 && (
+                <button onClick={() => handleUnlink(version)} className="px-3 py-1 rounded-full bg-primary hover:bg-tertiary text-sm">
+                  Отделить
+                </button>
+              )}
+            </li>
+          ))}
+        </ul>
+      </div>
+
+      {otherTitles.length > 0 && (
+        <div>
+          <h2 className="text-2xl font-bold mb-2 text-text-primary">Объединить с другой игрой</h2>
+          <p className="text-sm text-text-secondary mb-2">
+            Если сканер не узнал в другой записи эту же игру, ее версии можно присоединить вручную.
+          </p>
+          <div className="flex gap-2">
+            <select
+              value={linkTarget}
+              onChange={(e) => setLinkTarget(e.target.value)}
+              className="flex-1 bg-secondary p-2 rounded border text-text-primary border-gray-700"
+            >
+              <option value="">Выберите игру...</option>
+              {otherTitles.map(title => (
+                <option key={title.path} value={title.path}>
+                  {title.name}{title.version ? ` (${title.version})` : ''}
+                </option>
+              ))}
+            </select>
+            <button
+              onClick={handleLink}
+              disabled={!linkTarget}
+              className="px-4 py-2 rounded-lg bg-primary hover:bg-tertiary text-sm disabled:opacity-50"
+            >
+              Объединить
+            </button>
+          </div>
+        </div>
+      )}
+    </div>
+  );
+}
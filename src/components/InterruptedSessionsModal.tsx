@@ -0,0 +1,95 @@
+import { Dialog, DialogPanel, DialogTitle, Transition, TransitionChild } from '@headlessui/react';
+import { Fragment, useEffect, useState } from 'react';
+import { getAllGamesFromDb, getInterruptedSessions, PlaySession, resolveInterruptedSession } from '../utils/db';
+import { formatPlaytime } from '../utils/formatters';
+
+// Сессии, оборванные падением лаунчера (режим interruptedSessions = 'ask').
+// Показывается при запуске, пока пользователь не решит судьбу каждой сессии.
+export function InterruptedSessionsModal() {
+  const [sessions, setSessions] = useState<PlaySession[]>([]);
+  const [gameNames, setGameNames] = useState<Record<string, string>>({});
+
+  useEffect(() => {
+    const load = async () => {
+      try {
+        const interrupted = await getInterruptedSessions();
+        if (interrupted.length === 0) return;
+        const games = await getAllGamesFromDb();
+        setGameNames(Object.fromEntries(games.map(game => [game.path, game.name])));
+        setSessions(interrupted);
+      } catch (e) {
+        console.error('Не удалось загрузить оборванные сессии:', e);
+      }
+    };
+    load();
+  }, []);
+
+  const handleResolve = async (session: PlaySession, keep: boolean) => {
+    await resolveInterruptedSession(session.id, keep);
+    setSessions(current => current.filter(s => s.id !== session.id));
+  };
+
+  const handleResolveAll = async (keep: boolean) => {
+    for (const session of sessions) {
+      await resolveInterruptedSession(session.id, keep);
+    }
+    setSessions([]);
+  };
+
+  // Старые версии игры не попадают в список названий, для них показываем имя файла
+  const gameName = (path: string) => gameNames[path] ?? path.split(/[\\/]/).pop() ?? path;
+
+  return (
+    <Transition appear show={sessions.length > 0} as={Fragment}>
+      <Dialog as="div" className="relative z-50" onClose={() => {}}>
+        <div className="fixed inset-0 bg-black bg-opacity-50" />
+        <div className="fixed inset-0 overflow-y-auto">
+          <div className="flex min-h-full items-center justify-center p-4 text-center">
+            <TransitionChild as={Fragment}
+          enter="ease-out duration-300"
+          enterFrom="opacity-0"
+          enterTo="opacity-100"
+          leave="ease-in duration-200"
+          leaveFrom="opacity-100"
+          leaveTo="opacity-0">
+              <DialogPanel className="w-full max-w-lg transform overflow-hidden rounded-2xl bg-gray-800 p-6 text-left align-middle shadow-xl transition-all">
+                <DialogTitle as="h3" className="text-lg font-medium leading-6 text-white">
+                  Оборванные игровые сессии
+                </DialogTitle>
+                <div className="mt-2">
+                  <p className="text-sm text-gray-400">
+                    Лаунчер был закрыт, пока шли эти игры. Оставить время до последней сохраненной точки или не учитывать его?
+                  </p>
+                </div>
+
+                <ul className="mt-4 space-y-2">
+                  {sessions.map(session => (
+                    <li key={session.id} className="flex items-center gap-3 p-3 rounded-lg bg-gray-700">
+                      <div className="flex-1 min-w-0">
+                        <p className="font-semibold text-white truncate" title={session.game_path}>{gameName(session.game_path)}</p>
+                        <p className="text-xs text-gray-400">{session.started_at}, {formatPlaytime(session.duration_seconds)}</p>
+                      </div>
+                      <button onClick={() => handleResolve(session, true)} className="text-sm px-3 py-1 rounded bg-blue-600 hover:bg-blue-500 text-white">
+                        Оставить
+                      </button>
+                      <button onClick={() => handleResolve(session, false)} className="text-sm px-3 py-1 rounded bg-gray-600 hover:bg-gray-500 text-white">
+                        Удалить
+                      </button>
+                    </li>
+                  ))}
+                </ul>
+
+                {sessions.length > 1 && (
+                  <div className="mt-4 flex justify-end gap-2">
+                    <button onClick={() => handleResolveAll(true)} className="bg-blue-600 hover:bg-blue-500 text-white font-bold py-2 px-4 rounded">Оставить все</button>
+                    <button onClick={() => handleResolveAll(false)} className="bg-gray-600 hover:bg-gray-500 text-white font-bold py-2 px-4 rounded">Удалить все</button>
+                  </div>
+                )}
+              </DialogPanel>
+            </TransitionChild>
+          </div>
+        </div>
+      </Dialog>
+    </Transition>
+  );
+}
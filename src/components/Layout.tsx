@@ -5,6 +5,8 @@ import { ArrowLeftOnRectangleIcon, ArrowRightOnRectangleIcon } from '@heroicons/
 import { useState } from 'react';
 import { Toaster } from 'react-hot-toast'; // Для уведомлений
 import React from 'react';
+import { InterruptedSessionsModal } from './InterruptedSessionsModal';
+import { PendingGamesPanel } from './PendingGamesPanel';
 
 function Layout() {
   const { folders, addFolder } = useFolders();
@@ -34,6 +36,8 @@ function Layout() {
           duration: 4000,
         }}
       />
+      <InterruptedSessionsModal />
+      <PendingGamesPanel />
 
       <aside 
         className={`bg-secondary text-text-primary flex flex-col transition-all duration-300 ease-in-out ${isSidebarExpanded ? 'w-48' : 'w-16'}`}
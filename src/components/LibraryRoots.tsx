@@ -0,0 +1,129 @@
+import { useEffect, useState } from 'react';
+import toast from 'react-hot-toast';
+import { deleteLibraryRoot, getLibraryRoots, LibraryRoot, saveLibraryRoot } from '../utils/db';
+import { rescanLibrary, RescanReport, selectGameDirectory } from '../utils/game-scanner';
+
+// Сохраненные папки библиотеки и их пересканирование: сначала отчет, потом применение
+export function LibraryRoots() {
+  const [roots, setRoots] = useState<LibraryRoot[]>([]);
+  const [report, setReport] = useState<RescanReport | null>(null);
+  const [isScanning, setIsScanning] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const loadRoots = async () => setRoots(await getLibraryRoots());
+
+  useEffect(() => {
+    loadRoots();
+  }, []);
+
+  const handleAdd = async () => {
+    const path = await selectGameDirectory();
+    if (!path) return;
+    try {
+      await saveLibraryRoot({ path, deep_search: false, dir_blacklist: [], file_blacklist: [] });
+      setError(null);
+      await loadRoots();
+    } catch (e) {
+      // Например, папка уже есть в списке
+      setError(String(e));
+    }
+  };
+
+  const handleRescan = async (apply: boolean) => {
+    setIsScanning(true);
+    try {
+      const result = await rescanLibrary(apply);
+      setError(null);
+      if (apply) {
+        setReport(null);
+        toast.success(`Добавлено игр: ${result.added.length}, перенесено: ${result.moved.length}`);
+      } else {
+        setReport(result);
+      }
+      await loadRoots();
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setIsScanning(false);
+    }
+  };
+
+  const hasChanges = report && (report.added.length > 0 || report.moved.length > 0);
+
+  return (
+    <div className="bg-secondary p-6 rounded-lg shadow-lg mb-8">
+      <h2 className="text-xl font-semibold mb-2 text-text-primary">Папки библиотеки</h2>
+      <p className="text-sm text-text-secondary mb-4">
+        Папки, в которых лаунчер ищет новые игры и игры, перемещенные на другой путь.
+      </p>
+
+      <ul className="mb-4 space-y-2">
+        {roots.map(root => (
+          <li key={root.id} className="flex items-center gap-3 bg-primary px-3 py-2 rounded-lg">
+            <span className="flex-1 font-mono truncate" title={root.path}>{root.path}</span>
+            <label className="flex items-center gap-2 text-sm text-text-secondary">
+              <input
+                type="checkbox"
+                checked={root.deep_search}
+                onChange={() => saveLibraryRoot({ ...root, deep_search: !root.deep_search }).then(loadRoots)}
+                className="accent-blue-500"
+              />
+              глубокий поиск
+            </label>
+            <span className="text-xs text-text-secondary w-36">{root.last_scan_at ?? 'не сканировалась'}</span>
+            <button onClick={() => root.id && deleteLibraryRoot(root.id).then(loadRoots)} className="text-sm text-red-400 hover:text-red-300">
+              Удалить
+            </button>
+          </li>
+        ))}
+      </ul>
+
+      <div className="flex flex-wrap gap-3">
+        <button onClick={handleAdd} className="px-4 py-2 rounded-lg font-semibold bg-primary hover:bg-tertiary">
+          Добавить папку
+        </button>
+        <button
+          onClick={() => handleRescan(false)}
+          disabled={isScanning || roots.length === 0}
+          className="px-4 py-2 rounded-lg font-semibold bg-blue-600 hover:bg-accent-primary disabled:bg-gray-500"
+        >
+          {isScanning ? 'Сканирование...' : 'Пересканировать'}
+        </button>
+      </div>
+      {error && <p className="mt-3 text-sm text-red-400">{error}</p>}
+
+      {report && (
+        <div className="mt-4 space-y-3 text-sm">
+          <RescanSection title="Новые игры" items={report.added.map(info => info.path)} />
+          <RescanSection title="Перемещены" items={report.moved.map(moved => `${moved.name}: ${moved.from} → ${moved.to}`)} />
+          <RescanSection title="Пропали (удалить можно на странице игры)" items={report.disappeared.map(game => `${game.name}: ${game.path}`)} />
+          <RescanSection title="Не распакованы" items={report.not_installed.map(archive => archive.path)} />
+          <RescanSection title="Недоступные папки" items={report.unavailable_roots} />
+          {hasChanges ? (
+            <button
+              onClick={() => handleRescan(true)}
+              disabled={isScanning}
+              className="px-4 py-2 rounded-lg font-semibold bg-green-600 hover:bg-green-500 disabled:bg-gray-500"
+            >
+              Добавить новые и перенести перемещенные
+            </button>
+          ) : (
+            <p className="text-text-secondary">Новых и перемещенных игр нет.</p>
+          )}
+        </div>
+      )}
+    </div>
+  );
+}
+
+function RescanSection({ title, items }: { title: string; items: string[] }) {
+  if (items.length === 0) return null;
+  return (
+    <div>
+      <h3 className="text-text-secondary mb-1">{title}: {items.length}</h3>
+      <ul className="font-mono text-xs space-y-1 max-h-40 overflow-y-auto">
+        {items.map(item => <li key={item} className="truncate" title={item}>{item}</li>)}
+      </ul>
+    </div>
+  );
+}
@@ -0,0 +1,69 @@
+import { useEffect, useState } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import toast from 'react-hot-toast';
+import { GameInfo, getPendingGames, resolvePendingGame } from '../utils/game-scanner';
+
+// Игры, которые наблюдатель нашел в режиме 'confirm' (libraryWatcher): добавить или пропустить
+export function PendingGamesPanel() {
+  const [games, setGames] = useState<GameInfo[]>([]);
+
+  useEffect(() => {
+    getPendingGames()
+      .then(setGames)
+      .catch(e => console.error('Не удалось загрузить найденные игры:', e));
+
+    const unlistenDetected = listen<GameInfo>('library-game-detected', event => {
+      setGames(current => current.some(game => game.path === event.payload.path) ? current : [...current, event.payload]);
+    });
+    return () => {
+      unlistenDetected.then(unlisten => unlisten());
+    };
+  }, []);
+
+  const handleResolve = async (game: GameInfo, accept: boolean) => {
+    try {
+      await resolvePendingGame(game.path, accept);
+      if (accept) toast.success(`${game.title ?? game.name} добавлена в библиотеку`);
+    } catch (e) {
+      toast.error(String(e));
+    }
+    setGames(current => current.filter(g => g.path !== game.path));
+  };
+
+  const handleResolveAll = async (accept: boolean) => {
+    for (const game of games) {
+      await resolvePendingGame(game.path, accept).catch(e => console.error(e));
+    }
+    setGames([]);
+  };
+
+  if (games.length === 0) return null;
+
+  return (
+    <div className="fixed bottom-4 right-4 z-40 w-96 max-h-96 overflow-y-auto rounded-lg bg-gray-800 p-4 shadow-xl">
+      <h3 className="font-semibold text-white mb-2">Найдены новые игры</h3>
+      <ul className="space-y-2">
+        {games.map(game => (
+          <li key={game.path} className="flex items-center gap-2 p-2 rounded bg-gray-700">
+            <div className="flex-1 min-w-0">
+              <p className="text-sm font-semibold text-white truncate">{game.title ?? game.name}{game.version ? ` ${game.version}` : ''}</p>
+              <p className="text-xs text-gray-400 truncate" title={game.path}>{game.path}</p>
+            </div>
+            <button onClick={() => handleResolve(game, true)} className="text-sm px-2 py-1 rounded bg-blue-600 hover:bg-blue-500 text-white">
+              Добавить
+            </button>
+            <button onClick={() => handleResolve(game, false)} className="text-sm px-2 py-1 rounded bg-gray-600 hover:bg-gray-500 text-white">
+              Пропустить
+            </button>
+          </li>
+        ))}
+      </ul>
+      {games.length > 1 && (
+        <div className="mt-3 flex justify-end gap-2">
+          <button onClick={() => handleResolveAll(true)} className="text-sm px-3 py-1 rounded bg-blue-600 hover:bg-blue-500 text-white">Добавить все</button>
+          <button onClick={() => handleResolveAll(false)} className="text-sm px-3 py-1 rounded bg-gray-600 hover:bg-gray-500 text-white">Пропустить все</button>
+        </div>
+      )}
+    </div>
+  );
+}
@@ -1,35 +1,81 @@
-import { createContext, useState, useContext, ReactNode } from 'react';
+import { createContext, useState, useContext, useEffect, ReactNode } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import toast from 'react-hot-toast';
+import { SessionCrash } from '../utils/db';
+
+// Должно совпадать с RunningGame в launcher.rs
+export interface RunningGame {
+  game_path: string;
+  name: string;
+  session_id: number;
+  pid: number;
+  started_at: string;
+}
+
+// Должно совпадать с GameExitInfo в launcher.rs
+export interface GameExitInfo {
+  game_path: string;
+  session_id: number;
+  pid: number;
+  exit_code: number | null;
+  duration_seconds: number;
+  crash: SessionCrash | null;
+}
 
 interface GameSessionContextType {
-  activeSessions: Map<string, number>; // Map<gamePath, pid>
-  addSession: (gamePath: string, pid: number) => void;
-  removeSession: (gamePath: string) => void;
+  activeSessions: Map<string, RunningGame>; // Map<gamePath, RunningGame>
   isGameRunning: (gamePath: string) => boolean;
+  stopGame: (gamePath: string) => Promise<void>;
 }
 
 const GameSessionContext = createContext<GameSessionContextType | undefined>(undefined);
 
+// Реестр запущенных игр живет в бэкенде, контекст только следит за ним
 export const GameSessionProvider = ({ children }: { children: ReactNode }) => {
-  const [activeSessions, setActiveSessions] = useState<Map<string, number>>(new Map());
+  const [activeSessions, setActiveSessions] = useState<Map<string, RunningGame>>(new Map());
 
-  const addSession = (gamePath: string, pid: number) => {
-    setActiveSessions(prev => new Map(prev).set(gamePath, pid));
-  };
+  useEffect(() => {
+    invoke<RunningGame[]>('list_running_games')
+      .then(games => setActiveSessions(new Map(games.map(game => [game.game_path, game]))))
+      .catch(err => console.error('Не удалось получить список запущенных игр:', err));
 
-  const removeSession = (gamePath: string) => {
-    setActiveSessions(prev => {
-      const newSessions = new Map(prev);
-      newSessions.delete(gamePath);
-      return newSessions;
+    const unlistenStarted = listen<RunningGame>('game-session-started', (event) => {
+      setActiveSessions(prev => new Map(prev).set(event.payload.game_path, event.payload));
     });
-  };
-  
+    const unlistenEnded = listen<GameExitInfo>('game-session-ended', (event) => {
+      setActiveSessions(prev => {
+        const newSessions = new Map(prev);
+        newSessions.delete(event.payload.game_path);
+        return newSessions;
+      });
+      const crash = event.payload.crash;
+      if (crash) {
+        const location = crash.script_file ? ` (${crash.script_file}:${crash.script_line})` : '';
+        toast.error(`Игра завершилась с ошибкой: ${crash.exception ?? 'см. ' + crash.report_path}${location}`);
+      }
+    });
+
+    return () => {
+      unlistenStarted.then(unlisten => unlisten());
+      unlistenEnded.then(unlisten => unlisten());
+    };
+  }, []);
+
   const isGameRunning = (gamePath: string) => {
     return activeSessions.has(gamePath);
   };
 
+  const stopGame = async (gamePath: string) => {
+    try {
+      await invoke<boolean>('stop_game', { gamePath });
+    } catch (e) {
+      console.error('Не удалось остановить игру:', e);
+    }
+  };
+
   return (
-    <GameSessionContext.Provider value={{ activeSessions, addSession, removeSession, isGameRunning }}>
+    <GameSessionContext.Provider value={{ activeSessions, isGameRunning, stopGame }}>
       {children}
     </GameSessionContext.Provider>
   );
@@ -41,4 +87,4 @@ export const useGameSession = () => {
     throw new Error('useGameSession must be used within a GameSessionProvider');
   }
   return context;
-};
\ No newline at end of file
+};
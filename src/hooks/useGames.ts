@@ -1,4 +1,5 @@
 import { useState, useEffect, useMemo } from 'react';
+import { listen } from '@tauri-apps/api/event';
 import { GameEntry, getAllGamesFromDb, getGamesByFolder } from '../utils/db';
 
 interface UseGamesOptions {
@@ -30,6 +31,12 @@ export function useGames({ folderId, sortBy = 'rating', showHidden = false }: Us
     };
     
     loadGames();
+
+    // Наблюдатель за папками библиотеки добавил игру в фоне
+    const unlistenAdded = listen('library-game-added', () => loadGames());
+    return () => {
+      unlistenAdded.then(unlisten => unlisten());
+    };
   }, [folderId]); // Перезагружаем, когда меняется folderId
 
   const processedGames = useMemo(() => {
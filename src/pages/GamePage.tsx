@@ -6,6 +6,7 @@ import { GamePageActions } from '../components/GamePage/Actions';
 import { GamePageTabs } from '../components/GamePageTabs';
 import { GameMetadata } from '../components/GameMetadata';
 import { GameSavesManager } from '../components/GameSavesManager';
+import { GameVersions } from '../components/GameVersions';
 import { motion } from 'framer-motion';
 import { useParams } from 'react-router-dom';
 import { useConfirm } from '../context/ConfirmContext';
@@ -75,6 +76,10 @@ export function GamePage() {
       title: 'Сохранения',
       content: <GameSavesManager game={game} />,
     },
+    {
+      title: 'Версии',
+      content: <GameVersions game={game} />,
+    },
   ];
 
   return (
@@ -6,8 +6,10 @@ import { MagnifyingGlassIcon, BellIcon, EyeIcon, EyeSlashIcon } from '@heroicons
 import { useParams } from 'react-router-dom';
 import { useFolders } from '../hooks/useFolders';
 import { addGameToDb, GameEntry, getSetting } from '../utils/db';
-import { scanForGames, selectGameDirectory } from '../utils/game-scanner';
+import { ArchiveCandidate, cancelInstall, cancelScan, InstallProgress, installFromArchive, scanForGames, ScanProgress, selectGameDirectory } from '../utils/game-scanner';
+import { formatSize } from '../utils/formatters';
 import { ScanButtonWithOption } from '../components/buttons/ScanWithOption';
+import { extractExeIcons } from '../utils/icon-manager';
 import { ProfileMenu } from '../components/ProfileMenu';
 
 function LibraryPage() {
@@ -18,22 +20,65 @@ function LibraryPage() {
 
   const [isScanning, setIsScanning] = useState(false);
   const [isFullScan, setIsFullScan] = useState(false);
+  const [scanId, setScanId] = useState<number | null>(null);
+  const [scanProgress, setScanProgress] = useState<ScanProgress | null>(null);
+  const [notInstalled, setNotInstalled] = useState<ArchiveCandidate[]>([]);
+  // Установка из архива: одна за раз
+  const [installing, setInstalling] = useState<{ archivePath: string; installId: number | null; progress: InstallProgress | null } | null>(null);
+  const [installError, setInstallError] = useState<string | null>(null);
+
+    const handleInstall = async (archive: ArchiveCandidate) => {
+        // По умолчанию ставим рядом с архивом
+        const archiveDir = archive.path.replace(/[\\/][^\\/]*$/, '');
+        const rootPath = await selectGameDirectory(archiveDir);
+        if (!rootPath) return;
+        setInstallError(null);
+        setInstalling({ archivePath: archive.path, installId: null, progress: null });
+        try {
+            const outcome = await installFromArchive(archive.path, rootPath, {
+                onStarted: installId => setInstalling(current => current && { ...current, installId }),
+                onProgress: progress => setInstalling(current => current && { ...current, progress }),
+            });
+            if (!outcome.cancelled) {
+                setNotInstalled(current => current.filter(a => a.path !== archive.path));
+            }
+            if (outcome.errors.length > 0) {
+                setInstallError(`${archive.title} распакована в ${outcome.game_dir}, но: ${outcome.errors.join('; ')}`);
+            }
+        } catch (e) {
+            setInstallError(`${archive.title}: ${e}`);
+        } finally {
+            setInstalling(null);
+        }
+    };
 
     const handleScanDirectory = async () => {
         const selectedDir = await selectGameDirectory();
         if (selectedDir) {
             setIsScanning(true);
-            const scannedRaw = await scanForGames(selectedDir, isFullScan);
+            const scannedRaw = await scanForGames(selectedDir, isFullScan, {
+                onStarted: setScanId,
+                onProgress: setScanProgress,
+                onArchives: setNotInstalled,
+            });
+            setScanId(null);
+            setScanProgress(null);
             
             for (const scannedGame of scannedRaw) {
                 const newGameEntry: GameEntry = {
-                    name: scannedGame.name.replace(/\.(exe|py|sh|bat|cmd)$/i, ''),
+                    name: scannedGame.title ?? scannedGame.name.replace(/\.(exe|py|sh|bat|cmd)$/i, ''),
                     path: scannedGame.path,
                     game_type: scannedGame.game_type,
                     play_time_seconds: 0,
                     rating: 0,
                     is_hidden: false,
                     version: scannedGame.version,
+                    engine: scannedGame.engine,
+                    name_source: scannedGame.name_source,
+                    version_source: scannedGame.version_source ?? undefined,
+                    save_directory: scannedGame.save_directory ?? undefined,
+                    build_name: scannedGame.build_name ?? undefined,
+                    engine_version: scannedGame.engine_version ?? undefined,
                     completion_percent: 0,
                     description: '',
                     icon_path: '',
@@ -42,6 +87,8 @@ function LibraryPage() {
                 };
                 await addGameToDb(newGameEntry);
             }
+            // Обложки по умолчанию - иконки из .exe новых игр
+            await extractExeIcons().catch(e => console.error('Не удалось извлечь иконки:', e));
             setIsScanning(false);
         }
     };
@@ -127,11 +174,58 @@ function LibraryPage() {
                 isFullScan={isFullScan}
                 setIsFullScan={setIsFullScan}
             />
+          {isScanning && scanId !== null && (
+            <div className="flex items-center gap-2 text-sm text-text-secondary max-w-xs">
+              <span className="truncate" title={scanProgress?.current_path}>
+                {scanProgress ? `Папок: ${scanProgress.dirs_visited}, найдено: ${scanProgress.candidates}` : 'Сканирование...'}
+              </span>
+              <button onClick={() => cancelScan(scanId)} className="px-3 py-1 rounded-full bg-primary hover:bg-tertiary">
+                Стоп
+              </button>
+            </div>
+          )}
           <button className="p-2 rounded-full hover:bg-primary"><BellIcon className="w-6 h-6" /></button>
           <ProfileMenu />
         </div>
       </header>
       
+      {notInstalled.length > 0 && <section className="mb-12">
+        <div className="flex justify-between items-center mb-4">
+          <h2 className="text-2xl font-semibold">Не распакованы</h2>
+          <button onClick={() => setNotInstalled([])} className="text-sm text-text-secondary hover:text-text-primary">Скрыть</button>
+        </div>
+        <ul className="space-y-2">
+          {notInstalled.map(archive => (
+            <li key={archive.path} className="flex items-center gap-4 bg-secondary px-4 py-3 rounded-lg">
+              <span className="font-semibold">{archive.title}</span>
+              {archive.version && <span className="text-sm text-text-secondary">{archive.version}</span>}
+              <span className="text-sm text-text-secondary">{archive.engine}</span>
+              <span className="flex-1 text-sm text-text-secondary truncate" title={archive.path}>{archive.path}</span>
+              <span className="text-sm text-text-secondary">{formatSize(archive.size)}</span>
+              {installing?.archivePath === archive.path ? (
+                <>
+                  <span className="text-sm text-text-secondary" title={installing.progress?.current_file}>
+                    {installing.progress
+                      ? `${formatSize(installing.progress.extracted_bytes)} / ${formatSize(installing.progress.total_bytes)}`
+                      : 'Распаковка...'}
+                  </span>
+                  {installing.installId !== null && (
+                    <button onClick={() => cancelInstall(installing.installId!)} className="px-3 py-1 rounded-full bg-primary hover:bg-tertiary text-sm">
+                      Стоп
+                    </button>
+                  )}
+                </>
+              ) : archive.format === 'zip' && (
+                <button onClick={() => handleInstall(archive)} disabled={installing !== null} className="px-3 py-1 rounded-full bg-primary hover:bg-tertiary text-sm disabled:opacity-50">
+                  Установить
+                </button>
+              )}
+            </li>
+          ))}
+        </ul>
+        {installError && <p className="mt-2 text-sm text-red-400">{installError}</p>}
+      </section>}
+
       {recentGames.length > 0 && <section>
         <h2 className="text-2xl font-semibold mb-4">Недавно играли</h2>
         {loading ? <p>Загрузка...</p> : (
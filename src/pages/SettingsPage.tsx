@@ -1,31 +1,70 @@
 import { useState, useEffect } from 'react';
-import { getSetting, setSetting } from '../utils/db';
+import { deleteScanRule, getScanRules, getSetting, resetScanRules, saveScanRule, ScanRule, setSetting } from '../utils/db';
+import { LibraryWatcherMode } from '../utils/game-scanner';
+import { LibraryRoots } from '../components/LibraryRoots';
 
 const SETTINGS_KEYS = {
     GRID_SMALL: 'gridSmall',
     GRID_LARGE: 'gridLarge',
     POSTER_RATIO: 'posterRatio',
+    LIBRARY_WATCHER: 'libraryWatcher',
+    INTERRUPTED_SESSIONS: 'interruptedSessions',
 };
 const RATIOS = ['2/3', '3/4', '4/3', '1/1', '16/9', '9/16']; 
+const WATCHER_MODES: { value: LibraryWatcherMode; label: string }[] = [
+    { value: 'off', label: 'Выключено' },
+    { value: 'confirm', label: 'Спрашивать' },
+    { value: 'auto', label: 'Добавлять сразу' },
+];
+// Что делать с сессиями, оборванными падением лаунчера (см. recover_interrupted_sessions)
+const INTERRUPTED_SESSION_MODES = [
+    { value: 'close', label: 'Учитывать до последней точки' },
+    { value: 'ask', label: 'Спрашивать' },
+];
 
 function SettingsPage() {
   const [gridSmall, setGridSmall] = useState(4);
   const [gridLarge, setGridLarge] = useState(6);
   const [posterRatio, setPosterRatio] = useState('2/3');
+  const [watcherMode, setWatcherMode] = useState<LibraryWatcherMode>('off');
+  const [interruptedMode, setInterruptedMode] = useState('close');
+  const [scanRules, setScanRules] = useState<ScanRule[]>([]);
+  const [newRule, setNewRule] = useState<ScanRule>({ target: 'file', kind: 'glob', pattern: '', enabled: true, is_builtin: false });
+  const [ruleError, setRuleError] = useState<string | null>(null);
+
+  const loadScanRules = async () => setScanRules(await getScanRules());
 
   useEffect(() => {
     const loadSettings = async () => {
       const small = await getSetting(SETTINGS_KEYS.GRID_SMALL);
       const large = await getSetting(SETTINGS_KEYS.GRID_LARGE);
       const ratio = await getSetting(SETTINGS_KEYS.POSTER_RATIO);
+      const watcher = await getSetting(SETTINGS_KEYS.LIBRARY_WATCHER);
+      const interrupted = await getSetting(SETTINGS_KEYS.INTERRUPTED_SESSIONS);
 
       if (small) setGridSmall(parseInt(small, 10));
       if (large) setGridLarge(parseInt(large, 10));
       if (ratio) setPosterRatio(ratio);
+      if (watcher) setWatcherMode(watcher as LibraryWatcherMode);
+      if (interrupted) setInterruptedMode(interrupted);
     };
     loadSettings();
+    loadScanRules();
   }, []);
 
+  // Ошибки (например, неверное регулярное выражение) приходят из Rust
+  const handleSaveRule = async (rule: ScanRule) => {
+    try {
+      await saveScanRule(rule);
+      setRuleError(null);
+      await loadScanRules();
+      return true;
+    } catch (e) {
+      setRuleError(String(e));
+      return false;
+    }
+  };
+
   const handleSaveSetting = async (key: string, value: string | number) => {
     await setSetting(key, value.toString());
     console.log(`Настройка сохранена: ${key} = ${value}`);
@@ -85,6 +124,98 @@ function SettingsPage() {
                 ))}
             </div>
         </div>
+
+        <div className="mt-8">
+            <h3 className="text-text-secondary mb-2">Следить за папками библиотеки (новые игры после распаковки):</h3>
+            <div className="flex flex-wrap gap-3">
+                {WATCHER_MODES.map(mode => (
+                    <button
+                        key={mode.value}
+                        onClick={() => {setWatcherMode(mode.value); handleSaveSetting(SETTINGS_KEYS.LIBRARY_WATCHER, mode.value)}}
+                        className={`px-4 py-2 rounded-lg font-semibold transition ${mode.value === watcherMode ? 'bg-green-600 text-text-primary' : 'bg-primary hover:bg-tertiary'}`}
+                    >
+                        {mode.label}
+                    </button>
+                ))}
+            </div>
+        </div>
+
+        <div className="mt-8">
+            <h3 className="text-text-secondary mb-2">Игровые сессии, оборванные закрытием лаунчера:</h3>
+            <div className="flex flex-wrap gap-3">
+                {INTERRUPTED_SESSION_MODES.map(mode => (
+                    <button
+                        key={mode.value}
+                        onClick={() => {setInterruptedMode(mode.value); handleSaveSetting(SETTINGS_KEYS.INTERRUPTED_SESSIONS, mode.value)}}
+                        className={`px-4 py-2 rounded-lg font-semibold transition ${mode.value === interruptedMode ? 'bg-green-600 text-text-primary' : 'bg-primary hover:bg-tertiary'}`}
+                    >
+                        {mode.label}
+                    </button>
+                ))}
+            </div>
+        </div>
+      </div>
+
+      <LibraryRoots />
+
+      <div className="bg-secondary p-6 rounded-lg shadow-lg mb-8">
+        <h2 className="text-xl font-semibold mb-2 text-text-primary">Правила сканирования</h2>
+        <p className="text-sm text-text-secondary mb-4">
+          Папки и файлы, которые сканер пропускает. Шаблоны не зависят от регистра, в glob * - любые символы, ? - один символ.
+          Шаблоны сравниваются только с именем файла или папки, путь вида art/raw в них указать нельзя.
+          Шаблоны для отдельной папки можно записать в файл .rvnignore внутри нее: по одному на строку, с / на конце - только папки,
+          действуют на любой глубине (пустой файл исключает всю папку).
+        </p>
+
+        <ul className="mb-4 space-y-2">
+          {scanRules.map(rule => (
+            <li key={rule.id} className="flex items-center gap-3 bg-primary px-3 py-2 rounded-lg">
+              <input
+                type="checkbox"
+                checked={rule.enabled}
+                onChange={() => handleSaveRule({ ...rule, enabled: !rule.enabled })}
+                className="accent-blue-500"
+              />
+              <span className="text-xs text-text-secondary w-12">{rule.target === 'dir' ? 'папка' : 'файл'}</span>
+              <span className="text-xs text-text-secondary w-12">{rule.kind}</span>
+              <span className={`flex-1 font-mono ${rule.enabled ? 'text-text-primary' : 'text-text-secondary line-through'}`}>{rule.pattern}</span>
+              {rule.is_builtin && <span className="text-xs text-text-secondary">встроенное</span>}
+              <button onClick={() => rule.id && deleteScanRule(rule.id).then(loadScanRules)} className="text-sm text-red-400 hover:text-red-300">
+                Удалить
+              </button>
+            </li>
+          ))}
+        </ul>
+
+        <div className="flex flex-wrap gap-3 items-center">
+          <select value={newRule.target} onChange={(e) => setNewRule({ ...newRule, target: e.target.value as ScanRule['target'] })} className="bg-primary px-3 py-2 rounded-lg">
+            <option value="file">Файл</option>
+            <option value="dir">Папка</option>
+          </select>
+          <select value={newRule.kind} onChange={(e) => setNewRule({ ...newRule, kind: e.target.value as ScanRule['kind'] })} className="bg-primary px-3 py-2 rounded-lg">
+            <option value="exact">Точное имя</option>
+            <option value="glob">Glob</option>
+            <option value="regex">Регулярное выражение</option>
+          </select>
+          <input
+            type="text"
+            placeholder="UnityCrashHandler*.exe"
+            value={newRule.pattern}
+            onChange={(e) => setNewRule({ ...newRule, pattern: e.target.value })}
+            className="flex-1 bg-primary px-3 py-2 rounded-lg font-mono focus:outline-none focus:ring-2 focus:ring-blue-500"
+          />
+          <button
+            onClick={async () => { if (await handleSaveRule(newRule)) setNewRule({ ...newRule, pattern: '' }); }}
+            disabled={!newRule.pattern.trim()}
+            className="px-4 py-2 rounded-lg font-semibold bg-blue-600 hover:bg-accent-primary disabled:bg-gray-500"
+          >
+            Добавить
+          </button>
+          <button onClick={() => resetScanRules().then(loadScanRules)} className="px-4 py-2 rounded-lg font-semibold bg-primary hover:bg-tertiary">
+            Сбросить встроенные
+          </button>
+        </div>
+        {ruleError && <p className="mt-3 text-sm text-red-400">{ruleError}</p>}
       </div>
     </div>
   );
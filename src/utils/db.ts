@@ -14,8 +14,22 @@ export interface GameEntry {
     rating: number;
     is_hidden: boolean;
     completion_percent: number;
+    engine?: GameEngine;
+    // Откуда взяты имя и версия: 'file' / 'path' (угадано сканером), 'options.rpy' или 'user'
+    name_source?: string;
+    version_source?: string;
+    save_directory?: string;
+    build_name?: string;
+    engine_version?: string;
+    // Ключ названия, объединяющий установленные версии одной игры.
+    // db_get_games возвращает только текущие версии с общим временем игры по всем версиям.
+    title_key?: string;
+    is_current_version?: boolean;
 }
 
+// Движки, которые бэкенд определяет по структуре папки игры
+export type GameEngine = 'renpy' | 'rpgmaker' | 'unity' | 'godot' | 'kirikiri' | 'html';
+
 export interface Folder {
     id: number;
     name: string;
@@ -93,6 +107,221 @@ export async function setSetting(key: string, value: string): Promise<void> {
     await invoke('db_set_setting', { key, value });
 }
 
+/** Все установленные версии названия игры, от новой к старой. */
+export async function getGameVersions(gamePath: string): Promise<GameEntry[]> {
+    return await invoke('db_get_game_versions', { gamePath });
+}
+
+export async function setCurrentVersion(gamePath: string): Promise<void> {
+    await invoke('db_set_current_version', { gamePath });
+}
+
+/** Присоединяет все версии gamePath к названию titlePath. */
+export async function linkGameVersion(gamePath: string, titlePath: string): Promise<void> {
+    await invoke('db_link_game_version', { gamePath, titlePath });
+}
+
+/** Отделяет версию в самостоятельную игру. */
+export async function unlinkGameVersion(gamePath: string): Promise<void> {
+    await invoke('db_unlink_game_version', { gamePath });
+}
+
 export async function updateGameCompletion(path: string, percent: number): Promise<void> {
     await invoke('db_update_game_completion', { path, percent });
-}
\ No newline at end of file
+}
+
+// Должно совпадать с PlaySession в Rust
+export interface PlaySession {
+    id: number;
+    game_path: string;
+    started_at: string;
+    ended_at?: string;
+    duration_seconds: number;
+    exit_code?: number;
+    game_version?: string;
+    last_checkpoint?: string;
+    interrupted: boolean;
+    // Заполнено, если за сессию игра упала и оставила отчет Ren'Py
+    crash?: SessionCrash;
+}
+
+// Должно совпадать с SessionCrash в Rust
+export interface SessionCrash {
+    report_path: string;
+    exception?: string;
+    script_file?: string;
+    script_line?: number;
+}
+
+export async function getPlaySessions(gamePath: string): Promise<PlaySession[]> {
+    return await invoke('db_get_play_sessions', { gamePath });
+}
+
+export async function updatePlaySession(id: number, startedAt: string, endedAt: string, durationSeconds?: number): Promise<void> {
+    await invoke('db_update_play_session', { id, startedAt, endedAt, durationSeconds });
+}
+
+export async function deletePlaySession(id: number): Promise<void> {
+    await invoke('db_delete_play_session', { id });
+}
+
+export async function getInterruptedSessions(): Promise<PlaySession[]> {
+    return await invoke('db_get_interrupted_sessions');
+}
+
+export async function resolveInterruptedSession(id: number, keep: boolean): Promise<void> {
+    await invoke('db_resolve_interrupted_session', { id, keep });
+}
+
+// Должно совпадать с LaunchProfile в Rust
+export interface LaunchProfile {
+    id?: number;
+    game_path: string;
+    name: string;
+    arguments: string[];
+    env_vars: Record<string, string>;
+    working_dir?: string;
+    wrapper?: string;
+    is_default: boolean;
+}
+
+export async function getLaunchProfiles(gamePath: string): Promise<LaunchProfile[]> {
+    return await invoke('db_get_launch_profiles', { gamePath });
+}
+
+export async function saveLaunchProfile(profile: LaunchProfile): Promise<number> {
+    return await invoke('db_save_launch_profile', { profile });
+}
+
+export async function deleteLaunchProfile(id: number): Promise<void> {
+    await invoke('db_delete_launch_profile', { id });
+}
+
+export async function setDefaultLaunchProfile(gamePath: string, id: number): Promise<void> {
+    await invoke('db_set_default_launch_profile', { gamePath, id });
+}
+
+// Должно совпадать с Runner в Rust
+export interface Runner {
+    id?: number;
+    name: string;
+    kind: 'wine' | 'proton';
+    path: string;
+    is_default: boolean;
+}
+
+export async function getRunners(): Promise<Runner[]> {
+    return await invoke('db_get_runners');
+}
+
+export async function saveRunner(runner: Runner): Promise<number> {
+    return await invoke('db_save_runner', { runner });
+}
+
+export async function deleteRunner(id: number): Promise<void> {
+    await invoke('db_delete_runner', { id });
+}
+
+export async function setGameRunner(gamePath: string, runnerId: number | null, prefixMode: 'shared' | 'game' | null): Promise<void> {
+    await invoke('db_set_game_runner', { gamePath, runnerId, prefixMode });
+}
+
+export async function detectRunners(): Promise<Omit<Runner, 'id' | 'is_default'>[]> {
+    return await invoke('detect_runners');
+}
+
+// Должно совпадать с GameHook в Rust. game_path отсутствует у глобальных хуков.
+export interface GameHook {
+    id?: number;
+    game_path?: string;
+    stage: 'pre_launch' | 'post_exit';
+    command: string;
+    timeout_seconds: number;
+    abort_on_failure: boolean;
+    enabled: boolean;
+    sort_order: number;
+}
+
+export interface HookRun {
+    id: number;
+    hook_id: number;
+    game_path: string;
+    session_id?: number;
+    stage: 'pre_launch' | 'post_exit';
+    started_at: string;
+    duration_ms: number;
+    exit_code?: number;
+    timed_out: boolean;
+    output: string;
+}
+
+export async function getHooks(gamePath?: string): Promise<GameHook[]> {
+    return await invoke('db_get_hooks', { gamePath });
+}
+
+export async function saveHook(hook: GameHook): Promise<number> {
+    return await invoke('db_save_hook', { hook });
+}
+
+export async function deleteHook(id: number): Promise<void> {
+    await invoke('db_delete_hook', { id });
+}
+
+export async function getHookRuns(gamePath: string, limit?: number): Promise<HookRun[]> {
+    return await invoke('db_get_hook_runs', { gamePath, limit });
+}
+
+// null - следовать глобальной настройке autoBackupOnExit
+export async function setGameAutoBackup(gamePath: string, enabled: boolean | null): Promise<void> {
+    await invoke('db_set_game_auto_backup', { gamePath, enabled });
+}
+
+// Должно совпадать с LibraryRoot в Rust
+export interface LibraryRoot {
+    id?: number;
+    path: string;
+    deep_search: boolean;
+    // glob-шаблоны только для этой папки, в дополнение к общим правилам сканирования
+    dir_blacklist: string[];
+    file_blacklist: string[];
+    last_scan_at?: string;
+}
+
+export async function getLibraryRoots(): Promise<LibraryRoot[]> {
+    return await invoke('db_get_library_roots');
+}
+
+export async function saveLibraryRoot(root: LibraryRoot): Promise<number> {
+    return await invoke('db_save_library_root', { root });
+}
+
+export async function deleteLibraryRoot(id: number): Promise<void> {
+    await invoke('db_delete_library_root', { id });
+}
+
+// Должно совпадать с ScanRule в Rust
+export interface ScanRule {
+    id?: number;
+    target: 'dir' | 'file';
+    kind: 'exact' | 'glob' | 'regex';
+    pattern: string;
+    enabled: boolean;
+    is_builtin: boolean;
+}
+
+export async function getScanRules(): Promise<ScanRule[]> {
+    return await invoke('db_get_scan_rules');
+}
+
+export async function saveScanRule(rule: ScanRule): Promise<number> {
+    return await invoke('db_save_scan_rule', { rule });
+}
+
+export async function deleteScanRule(id: number): Promise<void> {
+    await invoke('db_delete_scan_rule', { id });
+}
+
+// Возвращает встроенные правила к исходному виду, правила пользователя остаются
+export async function resetScanRules(): Promise<void> {
+    await invoke('db_reset_scan_rules');
+}
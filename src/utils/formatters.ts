@@ -23,6 +23,21 @@ export function formatPlaytime(totalSeconds: number): string {
 }
 
 
+/**
+ * Размер файла в читаемом виде: "512 КБ", "1.4 ГБ".
+ * @param bytes Размер в байтах.
+ */
+export function formatSize(bytes: number): string {
+    const units = ['Б', 'КБ', 'МБ', 'ГБ', 'ТБ'];
+    let size = bytes;
+    let unit = 0;
+    while (size >= 1024 && unit < units.length - 1) {
+        size /= 1024;
+        unit++;
+    }
+    return `${unit === 0 ? size : size.toFixed(1)} ${units[unit]}`;
+}
+
 /**
  * Форматирует "сырое" имя игры в читаемый вид.
  * 1. Заменяет '_' на пробелы.
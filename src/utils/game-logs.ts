@@ -0,0 +1,18 @@
+import { invoke } from "@tauri-apps/api/core";
+
+// Лог вывода игры (stdout/stderr) за одну сессию
+export interface GameLog {
+  session_id: number;
+  path: string;
+  size_bytes: number;
+  modified: string;
+}
+
+export async function listGameLogs(gamePath: string): Promise<GameLog[]> {
+  return await invoke<GameLog[]>('list_game_logs', { gamePath });
+}
+
+// Возвращает последние maxKb килобайт лога (по умолчанию 64)
+export async function readGameLog(gamePath: string, sessionId: number, maxKb?: number): Promise<string> {
+  return await invoke<string>('read_game_log', { gamePath, sessionId, maxKb });
+}
@@ -1,22 +1,33 @@
 import { open as openDialog } from '@tauri-apps/plugin-dialog';
-import { invoke } from '@tauri-apps/api/core';
+import { Channel, invoke } from '@tauri-apps/api/core';
+import { GameEngine, GameEntry } from './db';
 
 export interface GameInfo {
   name: string;
   path: string;
   game_type: 'exe' | 'py' | 'sh' | 'bat' | 'cmd';
   version: string;
+  engine?: GameEngine;
+  // Название из options.rpy (Ren'Py), если удалось прочитать
+  title?: string;
+  name_source: 'file' | 'options.rpy';
+  version_source?: 'path' | 'options.rpy';
+  save_directory?: string;
+  build_name?: string;
+  engine_version?: string;
 }
 
 /**
  * Открывает диалог выбора директории.
+ * @param defaultPath Папка, открытая в диалоге изначально
  */
-export async function selectGameDirectory(): Promise<string | null> {
+export async function selectGameDirectory(defaultPath?: string): Promise<string | null> {
   try {
     const selectedPath = await openDialog({
       directory: true,
       multiple: false,
       title: 'Выберите директорию с играми',
+      defaultPath,
     });
     
     return typeof selectedPath === 'string' ? selectedPath : null;
@@ -26,31 +37,183 @@ export async function selectGameDirectory(): Promise<string | null> {
   }
 }
 
+// Должно совпадать с ScanProgress и ScanEvent в scanner.rs
+export interface ScanProgress {
+  dirs_visited: number;
+  candidates: number;
+  current_path: string;
+}
+
+// Должно совпадать с ArchiveCandidate в archives.rs: архив с игрой, которую еще не распаковали
+export interface ArchiveCandidate {
+  path: string;
+  size: number;
+  format: 'zip' | '7z' | 'rar';
+  title: string;
+  version?: string;
+  engine: GameEngine;
+  // Папка игры внутри архива ('' - корень)
+  game_dir: string;
+}
+
+type ScanEvent =
+  | { event: 'progress'; data: ScanProgress }
+  | { event: 'finished'; data: { games: GameInfo[]; archives: ArchiveCandidate[]; cancelled: boolean } };
+
+export interface ScanCallbacks {
+  // Вызывается сразу после запуска: id нужен для cancelScan
+  onStarted?: (scanId: number) => void;
+  onProgress?: (progress: ScanProgress) => void;
+  // Нераспакованные архивы с играми, найденные по пути
+  onArchives?: (archives: ArchiveCandidate[]) => void;
+}
+
 /**
  * Вызывает Rust-команду для рекурсивного сканирования директории.
+ * Что пропускать, сканер берет из правил в базе (см. getScanRules) и из файлов .rvnignore.
+ * После отмены возвращает игры, найденные до нее.
  * @param dirPath Путь к директории для сканирования.
  * @param deepSearch Включить глубокий поиск?
  */
-export async function scanForGames(dirPath: string, deepSearch: boolean): Promise<GameInfo[]> {
+export async function scanForGames(dirPath: string, deepSearch: boolean, callbacks: ScanCallbacks = {}): Promise<GameInfo[]> {
   try {
-    // Определяем блэклисты. Их можно будет настраивать в интерфейсе.
-    const dirBlacklist = ['renpy', 'lib', 'python-packages', 'common', 'saves', 'update', 'game', 'python27'];
-    const fileBlacklist = [
-      'python.exe', 'pythonw.exe', 'zsync.exe', 'zsyncmake.exe', 'librenpy.exe', 'librenpy-32.exe', 'renpy.exe'
-    ];
-
     console.log(`Начинаем сканирование "${dirPath}"... Глубокий поиск: ${deepSearch}`);
-    const games = await invoke<GameInfo[]>('scan_for_games_recursively', {
-      path: dirPath,
-      deepSearch: deepSearch,
-      dirBlacklist: dirBlacklist,
-      fileBlacklist: fileBlacklist,
+    const games = await new Promise<GameInfo[]>((resolve, reject) => {
+      // Finished может прийти раньше, чем invoke вернет id (пустая папка).
+      // Завершаемся только когда есть и то и другое, иначе onStarted выставит id уже закончившегося сканирования.
+      let started = false;
+      let finished: Extract<ScanEvent, { event: 'finished' }>['data'] | null = null;
+      const settle = () => {
+        if (!started || !finished) return;
+        if (finished.cancelled) {
+          console.log('Сканирование отменено.');
+        }
+        callbacks.onArchives?.(finished.archives);
+        resolve(finished.games);
+      };
+
+      const onEvent = new Channel<ScanEvent>();
+      onEvent.onmessage = (message) => {
+        if (message.event === 'progress') {
+          callbacks.onProgress?.(message.data);
+        } else {
+          finished = message.data;
+          settle();
+        }
+      };
+      invoke<number>('start_scan', {
+        path: dirPath,
+        deepSearch: deepSearch,
+        onEvent,
+      })
+        .then(scanId => {
+          callbacks.onStarted?.(scanId);
+          started = true;
+          settle();
+        })
+        .catch(reject);
     });
-    
+
     console.log(`Сканирование завершено. Найдено ${games.length} игр.`);
     return games;
   } catch (error) {
     console.error(`Ошибка при сканировании директории ${dirPath}:`, error);
     return [];
   }
-}
\ No newline at end of file
+}
+
+/** Останавливает сканирование; scanForGames вернет уже найденные игры. */
+export async function cancelScan(scanId: number): Promise<boolean> {
+  return await invoke<boolean>('cancel_scan', { scanId });
+}
+
+// Должно совпадать с InstallProgress и InstallEvent в install.rs
+export interface InstallProgress {
+  extracted_bytes: number;
+  total_bytes: number;
+  files_done: number;
+  files_total: number;
+  current_file: string;
+}
+
+export interface InstallOutcome {
+  // Папка, в которую распакована игра (null после отмены)
+  game_dir: string | null;
+  games: GameEntry[];
+  cancelled: boolean;
+  // Игра распакована, но, например, не записалась в базу: папка остается на месте
+  errors: string[];
+}
+
+type InstallEvent =
+  | { event: 'progress'; data: InstallProgress }
+  | { event: 'finished'; data: InstallOutcome }
+  | { event: 'failed'; data: string };
+
+export interface InstallCallbacks {
+  // Вызывается сразу после запуска: id нужен для cancelInstall
+  onStarted?: (installId: number) => void;
+  onProgress?: (progress: InstallProgress) => void;
+}
+
+/**
+ * Распаковывает .zip в новую папку внутри rootPath и добавляет найденные в ней игры.
+ * Единственная папка верхнего уровня в архиве не дублируется.
+ * При ошибке (папка уже существует, небезопасный путь в архиве) промис отклоняется.
+ */
+export async function installFromArchive(archivePath: string, rootPath: string, callbacks: InstallCallbacks = {}): Promise<InstallOutcome> {
+  return await new Promise<InstallOutcome>((resolve, reject) => {
+    const onEvent = new Channel<InstallEvent>();
+    onEvent.onmessage = (message) => {
+      if (message.event === 'progress') {
+        callbacks.onProgress?.(message.data);
+      } else if (message.event === 'finished') {
+        resolve(message.data);
+      } else {
+        reject(message.data);
+      }
+    };
+    invoke<number>('install_from_archive', { archivePath, rootPath, onEvent })
+      .then(installId => callbacks.onStarted?.(installId))
+      .catch(reject);
+  });
+}
+
+/** Останавливает установку; распакованные файлы удаляются. */
+export async function cancelInstall(installId: number): Promise<boolean> {
+  return await invoke<boolean>('cancel_install', { installId });
+}
+
+// Должно совпадать с RescanReport в library.rs
+export interface RescanReport {
+  added: GameInfo[];
+  disappeared: GameEntry[];
+  moved: { name: string; from: string; to: string }[];
+  not_installed: ArchiveCandidate[];
+  unavailable_roots: string[];
+}
+
+/**
+ * Пересканирует все сохраненные папки библиотеки.
+ * @param apply Сразу добавить новые игры и перенести перемещенные
+ */
+export async function rescanLibrary(apply: boolean): Promise<RescanReport> {
+  return await invoke<RescanReport>('rescan_library', { apply });
+}
+
+/**
+ * Режим фонового наблюдателя за папками библиотеки (настройка libraryWatcher):
+ * 'off' - выключен, 'auto' - сразу добавлять новые игры, 'confirm' - ждать подтверждения.
+ * Наблюдатель шлет события 'library-game-added' и 'library-game-detected' с GameInfo.
+ */
+export type LibraryWatcherMode = 'off' | 'auto' | 'confirm';
+
+/** Игры, найденные наблюдателем и ждущие подтверждения. */
+export async function getPendingGames(): Promise<GameInfo[]> {
+  return await invoke<GameInfo[]>('get_pending_games');
+}
+
+/** Добавляет игру из очереди в библиотеку (accept = true) или убирает ее из очереди. */
+export async function resolvePendingGame(gamePath: string, accept: boolean): Promise<void> {
+  await invoke('resolve_pending_game', { gamePath, accept });
+}
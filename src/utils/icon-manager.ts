@@ -1,7 +1,7 @@
 import { open as openDialog } from '@tauri-apps/plugin-dialog';
 import { copyFile, exists, BaseDirectory, mkdir } from '@tauri-apps/plugin-fs';
 import { appDataDir, join } from '@tauri-apps/api/path';
-import { convertFileSrc } from '@tauri-apps/api/core';
+import { convertFileSrc, invoke } from '@tauri-apps/api/core';
 
 const ICONS_DIR = 'icons';
 
@@ -59,3 +59,11 @@ export async function getIconUrl(relativeIconPath: string): Promise<string> {
     return '';
   }
 }
+
+/**
+ * Извлекает иконки из .exe для игр без иконки (или для одной игры)
+ * @returns Количество игр, получивших иконку
+ */
+export async function extractExeIcons(gamePath?: string): Promise<number> {
+  return await invoke<number>('extract_exe_icons', { gamePath });
+}
@@ -0,0 +1,10 @@
+import { invoke } from "@tauri-apps/api/core";
+
+// Ярлыки .desktop для меню приложений (Linux). Передается либо игра, либо папка целиком.
+export async function createDesktopShortcuts(target: { gamePath?: string; folderId?: number }): Promise<string[]> {
+  return await invoke<string[]>('create_desktop_shortcuts', target);
+}
+
+export async function removeDesktopShortcuts(target: { gamePath?: string; folderId?: number }): Promise<number> {
+  return await invoke<number>('remove_desktop_shortcuts', target);
+}